@@ -0,0 +1,404 @@
+use crate::db::Database;
+use crate::entity::account::Account;
+use crate::entity::csv_import::{
+    CsvAccountRow, CsvColumnMapping, CsvDuplicateKey, CsvImportOptions, CsvImportReport,
+    CsvPreview, CsvRowAction,
+};
+use crate::entity::ledger::AccountLedgerBalance;
+use crate::error::{AppError, AppResult};
+use crate::sqlite;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+
+/// 预览时返回的数据行数。
+const PREVIEW_ROWS: usize = 10;
+/// 分类、角色等多值单元格的分隔符。
+const LIST_SEPARATORS: &[char] = &[';', '；', ',', '，', '|', '、', '\n'];
+const UTF8_BOM: &str = "\u{feff}";
+/// 以这些字符开头的单元格会被 Excel 当作公式执行，导出时加 `'` 前缀；`'` 本身也需要转义以便原样导回。
+const FORMULA_PREFIXES: &[char] = &['=', '+', '-', '@', '\''];
+
+fn field_header(field: &str) -> Option<&'static str> {
+    match field {
+        "name" => Some("名称"),
+        "username" => Some("账号"),
+        "password" => Some("密码"),
+        "roles" => Some("角色区服"),
+        "description" => Some("备注"),
+        "liked" => Some("收藏状态"),
+        "ledger" => Some("账本余额"),
+        _ => None,
+    }
+}
+
+fn field_value(account: &Account, field: &str, ledger: &[AccountLedgerBalance]) -> String {
+    match field {
+        "name" => account.name.clone(),
+        "username" => account.username.clone(),
+        "password" => account.password.clone(),
+        "roles" => account
+            .roles
+            .iter()
+            .flatten()
+            .map(|role| format!("{}@{}", role.role_id, role.server))
+            .collect::<Vec<_>>()
+            .join(";"),
+        "description" => account.description.clone().unwrap_or_default(),
+        "liked" => if account.liked.unwrap_or(false) {
+            "是"
+        } else {
+            "否"
+        }
+        .to_string(),
+        "ledger" => ledger
+            .iter()
+            .map(|item| format!("{}:{}", item.currency, item.balance))
+            .collect::<Vec<_>>()
+            .join(";"),
+        _ => String::new(),
+    }
+}
+
+/// 防止公式注入：以公式字符开头的值加 `'` 前缀。
+fn escape_formula(value: String) -> String {
+    if value.starts_with(FORMULA_PREFIXES) {
+        format!("'{value}")
+    } else {
+        value
+    }
+}
+
+/// `escape_formula` 的逆操作，导入本程序导出的 CSV 时还原原始值。
+fn unescape_formula(value: &str) -> &str {
+    match value.strip_prefix('\'') {
+        Some(rest) if rest.starts_with(FORMULA_PREFIXES) => rest,
+        _ => value,
+    }
+}
+
+/// 按 `fields` 的顺序导出账号，返回导出的账号数。
+///
+/// 文件以 UTF-8 BOM 开头，便于 Excel 正确识别中文；以公式字符开头的单元格加 `'` 前缀。
+/// `ledger` 为各账号的账本余额，只在导出账本余额时使用。
+pub(crate) fn write_accounts(
+    path: &str,
+    accounts: &[Account],
+    fields: &[String],
+    ledger: &HashMap<i32, Vec<AccountLedgerBalance>>,
+) -> AppResult<usize> {
+    let fields: Vec<&str> = fields
+        .iter()
+        .map(String::as_str)
+        .filter(|field| field_header(field).is_some())
+        .collect();
+    if fields.is_empty() {
+        return Err(AppError::Validation("没有可导出的字段".to_string()));
+    }
+
+    let mut file = File::create(path)?;
+    file.write_all(UTF8_BOM.as_bytes())?;
+    let mut writer = csv::Writer::from_writer(file);
+    writer.write_record(fields.iter().filter_map(|field| field_header(field)))?;
+    for account in accounts {
+        let balances = account
+            .id
+            .and_then(|id| ledger.get(&id))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        writer.write_record(
+            fields
+                .iter()
+                .map(|field| escape_formula(field_value(account, field, balances))),
+        )?;
+    }
+    writer.flush()?;
+    Ok(accounts.len())
+}
+
+/// CSV 中的一条记录，`line` 为记录在文件中开始的行号（从 1 开始，含换行的单元格会占用多行）。
+pub(crate) struct CsvRecord {
+    pub(crate) line: usize,
+    pub(crate) cells: Vec<String>,
+}
+
+impl CsvRecord {
+    pub(crate) fn is_blank(&self) -> bool {
+        self.cells.iter().all(|value| value.trim().is_empty())
+    }
+}
+
+/// 读取全部记录（含表头），各行列数可以不同。
+///
+/// 只去掉表头单元格两端的空白；数据单元格原样返回，用户名、密码中的空白可能是有意的。
+pub(crate) fn read_records(path: &str) -> AppResult<Vec<CsvRecord>> {
+    let content = fs::read(path)?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content.as_slice());
+    let mut records = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let line = match record.position() {
+            // 记录的位置在跳过空行之前，需要补上其前面的空行数
+            Some(position) => {
+                let blank_lines = content[position.byte() as usize..]
+                    .iter()
+                    .take_while(|byte| matches!(byte, b'\r' | b'\n'))
+                    .filter(|byte| **byte == b'\n')
+                    .count();
+                position.line() as usize + blank_lines
+            }
+            None => records.len() + 1,
+        };
+        records.push(CsvRecord {
+            line,
+            cells: record.iter().map(str::to_string).collect(),
+        });
+    }
+    if let Some(header) = records.first_mut() {
+        for cell in &mut header.cells {
+            *cell = cell.trim_start_matches(UTF8_BOM).trim().to_string();
+        }
+    }
+    Ok(records)
+}
+
+fn suggest_mapping(headers: &[String]) -> CsvColumnMapping {
+    let find = |names: &[&str]| {
+        headers
+            .iter()
+            .position(|header| names.contains(&header.trim().to_lowercase().as_str()))
+    };
+    CsvColumnMapping {
+        name: find(&["名称", "账号名称", "name", "title"]),
+        username: find(&["账号", "帐号", "用户名", "username", "login", "user"]),
+        password: find(&["密码", "password"]),
+        description: find(&["备注", "说明", "description", "notes", "note"]),
+        categories: find(&["分类", "分组", "category", "categories", "group"]),
+        roles: find(&["角色区服", "角色", "roles", "role"]),
+    }
+}
+
+/// 读取 CSV 表头与前几行，供前端选择列映射。
+pub(crate) fn preview(path: &str) -> AppResult<CsvPreview> {
+    let mut records = read_records(path)?.into_iter().map(|record| record.cells);
+    let Some(headers) = records.next() else {
+        return Err(AppError::Validation("CSV 文件为空".to_string()));
+    };
+    let records: Vec<Vec<String>> = records.collect();
+    let suggested = suggest_mapping(&headers);
+    Ok(CsvPreview {
+        total_rows: records.len(),
+        rows: records.into_iter().take(PREVIEW_ROWS).collect(),
+        headers,
+        suggested,
+    })
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(LIST_SEPARATORS)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+/// 解析 `角色ID@区服`，角色 ID 中可以包含 `@`，以最后一个为准。
+fn parse_role(value: &str) -> Option<(String, String)> {
+    let (role_id, server) = value.rsplit_once('@')?;
+    let (role_id, server) = (role_id.trim(), server.trim());
+    if role_id.is_empty() || server.is_empty() {
+        return None;
+    }
+    Some((role_id.to_string(), server.to_string()))
+}
+
+/// 解析失败的行：`(行号, 名称, 原因)`。
+pub(crate) type CsvRowError = (usize, String, String);
+
+/// 按列映射解析 CSV（第一行为表头），返回可导入的行与解析失败的行。
+///
+/// 名称、备注、分类与角色去掉两端空白，用户名与密码保持原样（只还原导出时加的公式转义）。
+pub(crate) fn read_rows(
+    path: &str,
+    mapping: &CsvColumnMapping,
+) -> AppResult<(Vec<CsvAccountRow>, Vec<CsvRowError>)> {
+    let Some(name_column) = mapping.name else {
+        return Err(AppError::Validation("请指定“名称”对应的列".to_string()));
+    };
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for record in read_records(path)?.into_iter().skip(1) {
+        let line = record.line;
+        if record.is_blank() {
+            continue;
+        }
+        let raw = |column: Option<usize>| -> Option<String> {
+            column
+                .and_then(|column| record.cells.get(column))
+                .map(|value| unescape_formula(value).to_string())
+        };
+        let cell = |column: Option<usize>| raw(column).map(|value| value.trim().to_string());
+
+        let name = cell(Some(name_column)).unwrap_or_default();
+        if name.is_empty() {
+            errors.push((line, name, "名称为空".to_string()));
+            continue;
+        }
+
+        let mut roles = Vec::new();
+        let mut invalid_role = None;
+        for item in split_list(&cell(mapping.roles).unwrap_or_default()) {
+            match parse_role(item) {
+                Some(role) => roles.push(role),
+                None => {
+                    invalid_role = Some(item.to_string());
+                    break;
+                }
+            }
+        }
+        if let Some(item) = invalid_role {
+            errors.push((line, name, format!("角色格式应为“角色ID@区服”: {item}")));
+            continue;
+        }
+
+        rows.push(CsvAccountRow {
+            line,
+            categories: split_list(&cell(mapping.categories).unwrap_or_default())
+                .map(str::to_string)
+                .collect(),
+            username: raw(mapping.username),
+            password: raw(mapping.password),
+            description: cell(mapping.description).filter(|value| !value.is_empty()),
+            roles,
+            name,
+        });
+    }
+    Ok((rows, errors))
+}
+
+/// 按“导出设置”中的字段把所有账号导出为 CSV，返回导出的账号数。
+pub(crate) fn export_accounts(db: &Database, path: &str) -> AppResult<usize> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(AppError::Validation("请选择导出文件路径".to_string()));
+    }
+    let fields = sqlite::get_export_fields(db)?;
+    let accounts = sqlite::query_all_accounts(db)?;
+    let mut ledger: HashMap<i32, Vec<AccountLedgerBalance>> = HashMap::new();
+    if fields.iter().any(|field| field == "ledger") {
+        for balance in sqlite::query_ledger_balances(db, None)? {
+            ledger.entry(balance.account_id).or_default().push(balance);
+        }
+    }
+    write_accounts(path, &accounts, &fields, &ledger)
+}
+
+/// 按列映射导入 CSV，解析失败的行与导入结果一起按行号返回。
+pub(crate) fn import_accounts(
+    db: &Database,
+    options: &CsvImportOptions,
+) -> AppResult<CsvImportReport> {
+    let (rows, errors) = read_rows(options.path.trim(), &options.mapping)?;
+    let mut report = CsvImportReport {
+        dry_run: options.dry_run,
+        ..CsvImportReport::default()
+    };
+    for (line, name, message) in errors {
+        report.push(line, &name, CsvRowAction::Failed, Some(message));
+    }
+    sqlite::import_csv_accounts(
+        db,
+        &rows,
+        CsvDuplicateKey::Username,
+        options.duplicate,
+        &mut report,
+    )?;
+    report.rows.sort_by_key(|row| row.line);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_csv(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("auto-paste-test-{}-{name}.csv", std::process::id()))
+    }
+
+    fn account(name: &str, username: &str, password: &str) -> Account {
+        Account {
+            id: None,
+            name: name.to_string(),
+            username: username.to_string(),
+            password: password.to_string(),
+            sequence: None,
+            liked: None,
+            description: None,
+            last_update_time: None,
+            account_category_ids: None,
+            roles: None,
+        }
+    }
+
+    fn mapping() -> CsvColumnMapping {
+        CsvColumnMapping {
+            name: Some(0),
+            username: Some(1),
+            password: Some(2),
+            ..CsvColumnMapping::default()
+        }
+    }
+
+    #[test]
+    fn export_escapes_formulas_and_import_restores_them() {
+        let path = temp_csv("formula");
+        let accounts = [
+            account("=HYPERLINK(\"x\")", "@alice", "-1+1"),
+            account("'引号", " 空格 ", "'=kept"),
+        ];
+        let fields = ["name", "username", "password"].map(str::to_string);
+        write_accounts(path.to_str().unwrap(), &accounts, &fields, &HashMap::new()).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(
+            content.contains("'=HYPERLINK")
+                && content.contains("'@alice")
+                && content.contains("'-1+1")
+        );
+
+        let (rows, errors) = read_rows(path.to_str().unwrap(), &mapping()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(rows[0].name, "=HYPERLINK(\"x\")");
+        assert_eq!(rows[0].username.as_deref(), Some("@alice"));
+        assert_eq!(rows[0].password.as_deref(), Some("-1+1"));
+        assert_eq!(rows[1].name, "'引号");
+        assert_eq!(rows[1].username.as_deref(), Some(" 空格 "));
+        assert_eq!(rows[1].password.as_deref(), Some("'=kept"));
+    }
+
+    #[test]
+    fn rows_report_file_lines_and_keep_secrets_untrimmed() {
+        let path = temp_csv("lines");
+        fs::write(
+            &path,
+            "\u{feff} 名称 ,账号,密码\n\"多行\n名称\",a,b\n\n  乙  , bob ,\" pass \"\n,c,d\n",
+        )
+        .unwrap();
+
+        let records = read_records(path.to_str().unwrap()).unwrap();
+        assert_eq!(records[0].cells[0], "名称");
+        let (rows, errors) = read_rows(path.to_str().unwrap(), &mapping()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(rows.iter().map(|row| row.line).collect::<Vec<_>>(), [2, 5]);
+        assert_eq!(rows[1].name, "乙");
+        assert_eq!(rows[1].username.as_deref(), Some(" bob "));
+        assert_eq!(rows[1].password.as_deref(), Some(" pass "));
+        assert_eq!(errors, [(6, String::new(), "名称为空".to_string())]);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::db::Database;
+use crate::entity::backup::{
+    BackupArchive, BackupSummary, RestoreMode, BACKUP_FORMAT, BACKUP_VERSION,
+};
+use crate::entity::vault_document::{VaultDocument, VaultTransferSummary};
+use crate::error::{AppError, AppResult};
+use crate::sqlite;
+use crate::vault::{self, SealedData};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+fn archive_path(path: &str) -> AppResult<&Path> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(AppError::Validation("请选择备份文件路径".to_string()));
+    }
+    Ok(Path::new(path))
+}
+
+/// 把整个账号库与设置加密写入备份文件，返回备份内容概要。
+///
+/// 先写入同目录下的临时文件再改名，避免覆盖旧备份时中途失败留下不完整的文件。
+pub(crate) fn create_backup(
+    db: &Database,
+    path: &str,
+    passphrase: &str,
+) -> AppResult<BackupSummary> {
+    let path = archive_path(path)?;
+    let document = sqlite::export_vault(db)?;
+    let mut archive = BackupArchive {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        app_version: APP_VERSION.to_string(),
+        created_at: document.exported_at,
+        kdf: String::new(),
+        salt: String::new(),
+        payload: String::new(),
+    };
+    let sealed = vault::seal(
+        passphrase,
+        &serde_json::to_string(&document)?,
+        &archive.header_aad(),
+    )?;
+    archive.kdf = sealed.kdf;
+    archive.salt = sealed.salt;
+    archive.payload = sealed.ciphertext;
+
+    // 在完整文件名后追加后缀，不能替换扩展名，否则可能覆盖同名的其他文件
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    fs::write(&temp, serde_json::to_string_pretty(&archive)?)?;
+    if let Err(err) = fs::rename(&temp, path) {
+        let _ = fs::remove_file(&temp);
+        return Err(err.into());
+    }
+
+    Ok(BackupSummary {
+        app_version: archive.app_version,
+        created_at: archive.created_at,
+        contents: document.summary(),
+    })
+}
+
+/// 读取并解密备份文件，口令错误或文件被改动时返回错误。
+fn open_backup(path: &str, passphrase: &str) -> AppResult<(BackupArchive, VaultDocument)> {
+    let archive = BackupArchive::parse(&fs::read_to_string(archive_path(path)?)?)?;
+    let sealed = SealedData {
+        salt: archive.salt.clone(),
+        kdf: archive.kdf.clone(),
+        ciphertext: archive.payload.clone(),
+    };
+    let text = vault::unseal(&sealed, passphrase, &archive.header_aad())?;
+    let document = VaultDocument::parse(&text)?;
+    Ok((archive, document))
+}
+
+/// 校验备份文件并返回其内容概要，供用户确认后再恢复。
+pub(crate) fn inspect_backup(path: &str, passphrase: &str) -> AppResult<BackupSummary> {
+    let (archive, document) = open_backup(path, passphrase)?;
+    Ok(BackupSummary {
+        app_version: archive.app_version,
+        created_at: archive.created_at,
+        contents: document.summary(),
+    })
+}
+
+/// 校验并恢复备份，返回实际写入的数据量。
+pub(crate) fn restore_backup(
+    db: &Database,
+    path: &str,
+    passphrase: &str,
+    mode: RestoreMode,
+) -> AppResult<VaultTransferSummary> {
+    let (_, document) = open_backup(path, passphrase)?;
+    match mode {
+        RestoreMode::Replace => sqlite::replace_vault(db, &document),
+        RestoreMode::Merge => sqlite::import_vault(db, &document),
+    }
+}
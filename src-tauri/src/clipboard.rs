@@ -0,0 +1,82 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, ClipboardManager};
+use zeroize::Zeroizing;
+
+/// 等待清除的剪贴板内容；同一时间只跟踪最近一次复制。
+struct PendingClear {
+    generation: u64,
+    value: Zeroizing<String>,
+    previous: Option<String>,
+}
+
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+lazy_static::lazy_static! {
+    static ref PENDING_CLEAR: Mutex<Option<PendingClear>> = Mutex::new(None);
+}
+
+/// 写入剪贴板，并在 `clear_after_secs` 秒后清除；`0` 表示不自动清除。
+///
+/// 到期时只有剪贴板仍是本次写入的内容才会处理，并尽量恢复复制前的文本。
+pub(crate) fn copy_with_auto_clear(
+    app: &AppHandle,
+    value: String,
+    clear_after_secs: u64,
+) -> tauri::Result<()> {
+    let mut clipboard = app.clipboard_manager();
+    let current = clipboard.read_text().unwrap_or(None);
+
+    let mut pending = PENDING_CLEAR.lock().unwrap();
+    // 连续复制时，剪贴板里还是上一次的敏感值，应恢复的是更早之前的文本
+    let previous = match pending.take() {
+        Some(last) if current.as_deref() == Some(last.value.as_str()) => last.previous,
+        _ => current,
+    };
+
+    clipboard.write_text(value.clone())?;
+    if clear_after_secs == 0 {
+        return Ok(());
+    }
+
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    *pending = Some(PendingClear {
+        generation,
+        value: Zeroizing::new(value),
+        previous,
+    });
+    drop(pending);
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_secs(clear_after_secs));
+        clear_if_unchanged(&app, generation);
+    });
+
+    Ok(())
+}
+
+fn clear_if_unchanged(app: &AppHandle, generation: u64) {
+    let mut pending = PENDING_CLEAR.lock().unwrap();
+    let last = match pending.take() {
+        Some(last) if last.generation == generation => last,
+        other => {
+            // 已被更新的复制取代，交给新的计时器处理
+            *pending = other;
+            return;
+        }
+    };
+
+    let mut clipboard = app.clipboard_manager();
+    match clipboard.read_text() {
+        Ok(Some(text)) if text == *last.value => {
+            let restored = last.previous.unwrap_or_default();
+            if let Err(e) = clipboard.write_text(restored) {
+                println!("clear clipboard error: {:?}", e);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => println!("read clipboard error: {:?}", e),
+    }
+}
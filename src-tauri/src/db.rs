@@ -0,0 +1,140 @@
+use crate::error::AppResult;
+use rusqlite::Connection;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::time::Duration;
+
+/// 桌面端并发有限，少量连接即可让读操作不再排在写事务之后。
+const POOL_SIZE: usize = 4;
+/// 写锁被占用时等待的时间，超时后返回 `SQLITE_BUSY`。
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct PoolState {
+    idle: Vec<Connection>,
+    open: usize,
+}
+
+/// 同一数据库文件的连接池，连接按需创建，最多 `POOL_SIZE` 个。
+struct Pool {
+    path: PathBuf,
+    state: Mutex<PoolState>,
+    released: Condvar,
+}
+
+fn open_connection(path: &Path) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    // WAL 模式下读操作不会被写事务阻塞；journal_mode 会返回设置后的模式，需要按查询执行
+    conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    Ok(conn)
+}
+
+impl Pool {
+    fn acquire(self: &Arc<Self>) -> rusqlite::Result<PooledConnection> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(conn) = state.idle.pop() {
+                return Ok(PooledConnection::new(conn, self));
+            }
+
+            if state.open < POOL_SIZE {
+                state.open += 1;
+                drop(state);
+                return match open_connection(&self.path) {
+                    Ok(conn) => Ok(PooledConnection::new(conn, self)),
+                    Err(e) => {
+                        self.state.lock().unwrap().open -= 1;
+                        self.released.notify_one();
+                        Err(e)
+                    }
+                };
+            }
+
+            state = self.released.wait(state).unwrap();
+        }
+    }
+
+    fn release(&self, conn: Connection) {
+        self.state.lock().unwrap().idle.push(conn);
+        self.released.notify_one();
+    }
+}
+
+/// 从连接池借出的连接，离开作用域时自动归还。
+pub(crate) struct PooledConnection {
+    conn: Option<Connection>,
+    pool: Arc<Pool>,
+}
+
+impl PooledConnection {
+    fn new(conn: Connection, pool: &Arc<Pool>) -> Self {
+        PooledConnection {
+            conn: Some(conn),
+            pool: Arc::clone(pool),
+        }
+    }
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection already released")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut().expect("connection already released")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.release(conn);
+        }
+    }
+}
+
+/// 数据库句柄，由 Tauri 托管并通过 `State<Database>` 注入到命令中。
+///
+/// 切换账号库时整体替换内部连接池；仍在使用中的旧连接归还后随旧池一起关闭。
+pub(crate) struct Database {
+    pool: RwLock<Arc<Pool>>,
+}
+
+impl Database {
+    /// 打开数据库文件并立即建立一个连接，用于尽早发现路径或权限问题。
+    pub(crate) fn open(path: &Path) -> AppResult<Self> {
+        let conn = open_connection(path)?;
+        let pool = Arc::new(Pool {
+            path: path.to_path_buf(),
+            state: Mutex::new(PoolState {
+                idle: vec![conn],
+                open: 1,
+            }),
+            released: Condvar::new(),
+        });
+
+        Ok(Database {
+            pool: RwLock::new(pool),
+        })
+    }
+
+    pub(crate) fn get(&self) -> AppResult<PooledConnection> {
+        let pool = Arc::clone(&self.pool.read().unwrap());
+        Ok(pool.acquire()?)
+    }
+
+    pub(crate) fn path(&self) -> PathBuf {
+        self.pool.read().unwrap().path.clone()
+    }
+
+    /// 用另一个数据库的连接池替换当前连接池。
+    pub(crate) fn replace(&self, other: Database) {
+        *self.pool.write().unwrap() = other.pool.into_inner().unwrap();
+    }
+}
@@ -15,3 +15,11 @@ pub(crate) struct Account {
     pub(crate) account_category_ids: Option<Vec<i32>>,
     pub(crate) roles: Option<Vec<Role>>,
 }
+
+/// 可由后端直接复制到剪贴板的敏感字段。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum AccountField {
+    Username,
+    Password,
+}
@@ -1,4 +1,4 @@
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct AccountCategory {
@@ -6,4 +6,4 @@ pub(crate) struct AccountCategory {
     pub(crate) account_id: i32,
     pub(crate) group_id: i32,
     pub(crate) last_update_time: Option<String>,
-}
\ No newline at end of file
+}
@@ -0,0 +1,72 @@
+use super::vault_document::VaultTransferSummary;
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+
+/// 备份文件的格式标识。
+pub(crate) const BACKUP_FORMAT: &str = "auto-paste-backup";
+/// 备份文件外层结构的版本，内层账号库文档另有自己的版本号。
+pub(crate) const BACKUP_VERSION: u32 = 1;
+
+/// 加密备份文件。
+///
+/// `payload` 为用备份口令加密的账号库导出文档；其余字段为明文头部，作为附加认证数据参与校验，
+/// 任何一处被改动都会导致解密失败。
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct BackupArchive {
+    pub(crate) format: String,
+    pub(crate) version: u32,
+    pub(crate) app_version: String,
+    /// 备份时间（Unix 时间戳，秒）。
+    pub(crate) created_at: i64,
+    pub(crate) kdf: String,
+    pub(crate) salt: String,
+    pub(crate) payload: String,
+}
+
+impl BackupArchive {
+    /// 解析备份文件并校验格式标识与版本。
+    pub(crate) fn parse(text: &str) -> AppResult<Self> {
+        let value: serde_json::Value = serde_json::from_str(text)
+            .map_err(|_| AppError::Validation("不是有效的备份文件".to_string()))?;
+        if value.get("format").and_then(|v| v.as_str()) != Some(BACKUP_FORMAT) {
+            return Err(AppError::Validation("不是有效的备份文件".to_string()));
+        }
+        let version = value
+            .get("version")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| AppError::Validation("备份文件缺少版本号".to_string()))?;
+        if version == 0 || version > u64::from(BACKUP_VERSION) {
+            return Err(AppError::Validation(format!(
+                "不支持的备份文件版本 {version}（当前支持 1 - {BACKUP_VERSION}），请升级应用后再恢复"
+            )));
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// 参与认证的头部信息。盐与密钥派生参数被改动时会派生出错误的密钥，无需再纳入。
+    pub(crate) fn header_aad(&self) -> Vec<u8> {
+        format!(
+            "{}\n{}\n{}\n{}",
+            self.format, self.version, self.app_version, self.created_at
+        )
+        .into_bytes()
+    }
+}
+
+/// 恢复备份的方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum RestoreMode {
+    /// 清空当前账号库后恢复。
+    Replace,
+    /// 与“导入账号库”相同：账号追加，同名的分类、任务与副本合并。
+    Merge,
+}
+
+/// 校验通过的备份内容概要，或备份、恢复完成后的数据量。
+#[derive(Debug, Serialize)]
+pub(crate) struct BackupSummary {
+    pub(crate) app_version: String,
+    pub(crate) created_at: i64,
+    pub(crate) contents: VaultTransferSummary,
+}
@@ -1,4 +1,4 @@
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct Category {
@@ -8,4 +8,4 @@ pub(crate) struct Category {
     pub(crate) last_update_time: Option<String>,
     // 外部字段
     pub(crate) account_ids: Option<Vec<i32>>,
-}
\ No newline at end of file
+}
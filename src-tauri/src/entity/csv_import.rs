@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+/// CSV 列与账号字段的对应关系，值为列序号（从 0 开始），为空表示不导入该字段。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct CsvColumnMapping {
+    pub(crate) name: Option<usize>,
+    pub(crate) username: Option<usize>,
+    pub(crate) password: Option<usize>,
+    pub(crate) description: Option<usize>,
+    /// 分类名称，多个用 `;` `,` `|` 或 `、` 分隔，不存在的分类会自动创建。
+    pub(crate) categories: Option<usize>,
+    /// 角色列表，格式为 `角色ID@区服`，多个用分隔符分隔。
+    pub(crate) roles: Option<usize>,
+}
+
+/// 判断导入的账号与已有账号重复时比较的字段。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CsvDuplicateKey {
+    /// 只比较用户名，用于 CSV 导入。
+    Username,
+    /// 比较名称与用户名，用于其他密码管理器的导出：同一用户名常用于多个网站。
+    NameAndUsername,
+}
+
+/// 账号已存在时的处理方式，是否重复按 `CsvDuplicateKey` 判断。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CsvDuplicateStrategy {
+    /// 跳过该行。
+    #[default]
+    Skip,
+    /// 用该行已映射的字段覆盖已有账号，分类与角色只追加不删除。
+    Overwrite,
+    /// 仍然新建一个账号。
+    CreateNew,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CsvImportOptions {
+    pub(crate) path: String,
+    pub(crate) mapping: CsvColumnMapping,
+    #[serde(default)]
+    pub(crate) duplicate: CsvDuplicateStrategy,
+    /// 为 `true` 时只预演：在事务内执行后回滚，返回的报告与实际导入一致。
+    #[serde(default)]
+    pub(crate) dry_run: bool,
+}
+
+/// 读取 CSV 后供用户确认列映射的预览。
+#[derive(Debug, Serialize)]
+pub(crate) struct CsvPreview {
+    pub(crate) headers: Vec<String>,
+    /// 前若干行数据。
+    pub(crate) rows: Vec<Vec<String>>,
+    pub(crate) total_rows: usize,
+    /// 按表头名称猜测的列映射。
+    pub(crate) suggested: CsvColumnMapping,
+}
+
+/// 按列映射解析出的一行账号数据，`line` 为 CSV 中的行号（表头为第 1 行）。
+#[derive(Debug)]
+pub(crate) struct CsvAccountRow {
+    pub(crate) line: usize,
+    pub(crate) name: String,
+    pub(crate) username: Option<String>,
+    pub(crate) password: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) categories: Vec<String>,
+    /// `(角色 ID, 区服)`。
+    pub(crate) roles: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CsvRowAction {
+    Created,
+    Updated,
+    Skipped,
+    Failed,
+}
+
+/// 单行的处理结果。
+#[derive(Debug, Serialize)]
+pub(crate) struct CsvRowResult {
+    pub(crate) line: usize,
+    pub(crate) name: String,
+    pub(crate) action: CsvRowAction,
+    /// 跳过或失败的原因。
+    pub(crate) message: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct CsvImportReport {
+    pub(crate) dry_run: bool,
+    pub(crate) created: usize,
+    pub(crate) updated: usize,
+    pub(crate) skipped: usize,
+    pub(crate) failed: usize,
+    /// 新建的分类数。
+    pub(crate) categories_created: usize,
+    pub(crate) rows: Vec<CsvRowResult>,
+}
+
+impl CsvImportReport {
+    pub(crate) fn push(
+        &mut self,
+        line: usize,
+        name: &str,
+        action: CsvRowAction,
+        message: Option<String>,
+    ) {
+        match action {
+            CsvRowAction::Created => self.created += 1,
+            CsvRowAction::Updated => self.updated += 1,
+            CsvRowAction::Skipped => self.skipped += 1,
+            CsvRowAction::Failed => self.failed += 1,
+        }
+        self.rows.push(CsvRowResult {
+            line,
+            name: name.to_string(),
+            action,
+            message,
+        });
+    }
+}
@@ -7,4 +7,58 @@ pub(crate) struct Jx3Server {
     pub(crate) server: String,
     pub(crate) status: String,
     pub(crate) last_update_time: Option<String>,
+    /// 是否关注该区服，关注的区服状态变化时发送系统通知。
+    #[serde(default)]
+    pub(crate) watched: bool,
+}
+
+/// 同步时发现的区服变化；`previous_status` 为 `None` 表示新出现的区服。
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Jx3ServerChange {
+    pub(crate) previous_status: Option<String>,
+    pub(crate) current: Jx3Server,
+}
+
+impl Jx3ServerChange {
+    /// 已关注且状态确实发生了变化（不含新增区服、仅大区变化）。
+    pub(crate) fn is_watched_transition(&self) -> bool {
+        self.current.watched
+            && self
+                .previous_status
+                .as_ref()
+                .is_some_and(|previous| *previous != self.current.status)
+    }
+}
+
+/// 区服状态历史中的一条记录，`changed_at` 为 Unix 时间戳（秒）。
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Jx3ServerStatusRecord {
+    pub(crate) status: String,
+    pub(crate) changed_at: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Jx3ServerStatusShare {
+    pub(crate) status: String,
+    pub(crate) seconds: i64,
+    /// 占有记录时长的百分比（0～100）。
+    pub(crate) percentage: f64,
+}
+
+/// 统计窗口内各状态的持续时间；第一条历史记录之前的时间不计入 `observed_seconds`。
+#[derive(Debug, Serialize)]
+pub(crate) struct Jx3ServerUptime {
+    pub(crate) server: String,
+    pub(crate) from: i64,
+    pub(crate) to: i64,
+    pub(crate) observed_seconds: i64,
+    pub(crate) statuses: Vec<Jx3ServerStatusShare>,
+}
+
+/// 最近一次维护的开始与结束时间；维护尚未结束时 `ended_at` 为 `None`。
+#[derive(Debug, Serialize)]
+pub(crate) struct Jx3ServerMaintenance {
+    pub(crate) server: String,
+    pub(crate) started_at: Option<i64>,
+    pub(crate) ended_at: Option<i64>,
 }
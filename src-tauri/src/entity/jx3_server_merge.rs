@@ -0,0 +1,77 @@
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+
+/// 合服记录：`old_server` 自 `effective_date`（YYYY-MM-DD）起并入 `new_server`。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Jx3ServerMerge {
+    pub(crate) old_server: String,
+    pub(crate) new_server: String,
+    pub(crate) effective_date: String,
+    /// 最近一次迁移角色的时间（Unix 时间戳，秒），尚未迁移时为 `None`。
+    #[serde(default)]
+    pub(crate) applied_at: Option<i64>,
+}
+
+fn is_valid_date(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+    matches!(
+        (
+            year.parse::<u32>(),
+            month.parse::<u32>(),
+            day.parse::<u32>()
+        ),
+        (Ok(_), Ok(1..=12), Ok(1..=31))
+    )
+}
+
+impl Jx3ServerMerge {
+    pub(crate) fn validate(&self) -> AppResult<()> {
+        let (old_server, new_server) = (self.old_server.trim(), self.new_server.trim());
+        if old_server.is_empty() || new_server.is_empty() {
+            return Err(AppError::Validation(
+                "合服前后的区服名称不能为空".to_string(),
+            ));
+        }
+        if old_server == new_server {
+            return Err(AppError::Validation(format!(
+                "区服不能合并到自身: {old_server}"
+            )));
+        }
+        if !is_valid_date(self.effective_date.trim()) {
+            return Err(AppError::Validation(format!(
+                "合服日期格式应为 YYYY-MM-DD: {}",
+                self.effective_date
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct MigratedRole {
+    pub(crate) role_id: String,
+    pub(crate) old_server: String,
+    pub(crate) new_server: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct AffectedAccount {
+    pub(crate) account_id: i32,
+    pub(crate) name: String,
+    pub(crate) roles: Vec<MigratedRole>,
+}
+
+/// 应用合服记录的结果；`dry_run` 为 true 时只统计不写入。
+#[derive(Debug, Serialize)]
+pub(crate) struct MergeReport {
+    pub(crate) dry_run: bool,
+    pub(crate) applied_merges: Vec<Jx3ServerMerge>,
+    pub(crate) migrated_roles: usize,
+    pub(crate) affected_accounts: Vec<AffectedAccount>,
+}
@@ -0,0 +1,99 @@
+use super::role::Role;
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+
+/// 角色账本中的一笔记录，`amount` 为正表示收入，为负表示支出。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct LedgerEntry {
+    pub(crate) id: Option<i32>,
+    /// 角色表主键。
+    pub(crate) role_id: i32,
+    /// 发生时间（Unix 时间戳，秒）。
+    pub(crate) occurred_at: i64,
+    /// 货币或物品名称，如“金币”“五行石”。
+    pub(crate) currency: String,
+    pub(crate) amount: i64,
+    /// 对方角色（角色表主键），在小号之间转移时记录。
+    #[serde(default)]
+    pub(crate) counterparty_role_id: Option<i32>,
+    #[serde(default)]
+    pub(crate) note: Option<String>,
+}
+
+impl LedgerEntry {
+    pub(crate) fn validate(&self) -> AppResult<()> {
+        if self.currency.trim().is_empty() {
+            return Err(AppError::Validation("货币或物品名称不能为空".to_string()));
+        }
+        if self.amount == 0 {
+            return Err(AppError::Validation("数量不能为 0".to_string()));
+        }
+        if self.counterparty_role_id == Some(self.role_id) {
+            return Err(AppError::Validation("对方角色不能是自己".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// 在两个角色之间转移货币或物品，会在双方账本各记一笔。
+#[derive(Debug, Deserialize)]
+pub(crate) struct LedgerTransfer {
+    pub(crate) from_role_id: i32,
+    pub(crate) to_role_id: i32,
+    pub(crate) currency: String,
+    /// 转移数量，必须为正数。
+    pub(crate) amount: i64,
+    /// 为空时使用当前时间。
+    #[serde(default)]
+    pub(crate) occurred_at: Option<i64>,
+    #[serde(default)]
+    pub(crate) note: Option<String>,
+}
+
+/// 账本查询条件，均为空时返回全部记录。
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct LedgerQuery {
+    pub(crate) account_id: Option<i32>,
+    pub(crate) role_id: Option<i32>,
+    pub(crate) currency: Option<String>,
+}
+
+/// 账本记录的对方角色。
+#[derive(Debug, Serialize)]
+pub(crate) struct LedgerCounterparty {
+    pub(crate) id: i32,
+    pub(crate) role_id: String,
+    pub(crate) server: String,
+    pub(crate) account_name: String,
+}
+
+/// 带滚动余额的账本记录。余额按时间顺序累计到该笔为止，不受查询条件影响。
+#[derive(Debug, Serialize)]
+pub(crate) struct LedgerRecord {
+    pub(crate) entry: LedgerEntry,
+    pub(crate) role: Role,
+    pub(crate) account_name: String,
+    pub(crate) counterparty: Option<LedgerCounterparty>,
+    /// 该角色此货币的余额。
+    pub(crate) role_balance: i64,
+    /// 所属账号下所有角色此货币的余额。
+    pub(crate) account_balance: i64,
+}
+
+/// 单个角色某种货币的当前余额。
+#[derive(Debug, Serialize)]
+pub(crate) struct RoleLedgerBalance {
+    pub(crate) role: Role,
+    pub(crate) balance: i64,
+}
+
+/// 账号某种货币的当前余额及各角色明细。
+#[derive(Debug, Serialize)]
+pub(crate) struct AccountLedgerBalance {
+    pub(crate) account_id: i32,
+    pub(crate) account_name: String,
+    pub(crate) currency: String,
+    pub(crate) balance: i64,
+    pub(crate) roles: Vec<RoleLedgerBalance>,
+}
@@ -0,0 +1,30 @@
+use super::csv_import::CsvDuplicateStrategy;
+use serde::Deserialize;
+
+/// 支持导入的其他密码管理器导出格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PasswordImportFormat {
+    /// KeePass 2.x「导出为 KeePass XML (2.x)」。
+    KeepassXml,
+    /// Bitwarden 未加密的 JSON 导出。
+    BitwardenJson,
+    /// Chrome、Edge、Firefox、Safari 等浏览器导出的密码 CSV。
+    BrowserCsv,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PasswordImportOptions {
+    pub(crate) path: String,
+    pub(crate) format: PasswordImportFormat,
+    /// 默认仍然新建，避免不同网站的同名条目被当作重复跳过。
+    #[serde(default = "default_duplicate")]
+    pub(crate) duplicate: CsvDuplicateStrategy,
+    /// 为 `true` 时只预演，与 CSV 导入相同。
+    #[serde(default)]
+    pub(crate) dry_run: bool,
+}
+
+fn default_duplicate() -> CsvDuplicateStrategy {
+    CsvDuplicateStrategy::CreateNew
+}
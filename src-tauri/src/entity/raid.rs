@@ -0,0 +1,59 @@
+use super::role::Role;
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+
+/// 副本目录中的一项。同一副本的不同模式（如 10 人普通、25 人英雄）CD 独立，分别登记。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RaidInstance {
+    pub(crate) id: Option<i32>,
+    pub(crate) name: String,
+    /// 模式，如“25人英雄”；为空表示不区分模式。
+    #[serde(default)]
+    pub(crate) mode: Option<String>,
+    #[serde(default)]
+    pub(crate) sequence: Option<i32>,
+    /// 停用的副本不出现在副本选择中，已有的 CD 记录保留。
+    #[serde(default = "default_enabled")]
+    pub(crate) enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl RaidInstance {
+    pub(crate) fn validate(&self) -> AppResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(AppError::Validation("副本名称不能为空".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 带模式的完整名称，如“西津渡（25人英雄）”。
+    pub(crate) fn display_name(&self) -> String {
+        match self.mode.as_deref() {
+            Some(mode) if !mode.is_empty() => format!("{}（{}）", self.name, mode),
+            _ => self.name.clone(),
+        }
+    }
+}
+
+/// 角色的副本 CD 记录，`expires_at` 为获得 CD 后的下一次周常刷新时间。
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct RaidLockout {
+    pub(crate) raid_id: i32,
+    pub(crate) raid_name: String,
+    /// 角色表主键。
+    pub(crate) role_id: i32,
+    /// 获得 CD 的时间（Unix 时间戳，秒）。
+    pub(crate) locked_at: i64,
+    pub(crate) expires_at: i64,
+}
+
+/// 跨账号查看本周的副本 CD。
+#[derive(Debug, Serialize)]
+pub(crate) struct RaidLockoutDetail {
+    pub(crate) lockout: RaidLockout,
+    pub(crate) role: Role,
+    pub(crate) account_name: String,
+}
@@ -1,3 +1,4 @@
+use crate::error::{AppError, AppResult};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6,5 +7,80 @@ pub(crate) struct Role {
     pub(crate) account_id: Option<i32>,
     pub(crate) role_id: String,
     pub(crate) server: String,
+    /// 角色显示名，方便区分同一账号下的多个角色。
+    #[serde(default)]
+    pub(crate) name: Option<String>,
+    /// 门派。
+    #[serde(default)]
+    pub(crate) school: Option<String>,
+    /// 体型：成男、成女、正太、萝莉。
+    #[serde(default)]
+    pub(crate) body_type: Option<String>,
+    #[serde(default)]
+    pub(crate) level: Option<i32>,
+    /// 装备分数。
+    #[serde(default)]
+    pub(crate) equipment_score: Option<i32>,
+    #[serde(default)]
+    pub(crate) notes: Option<String>,
     pub(crate) last_update_time: Option<String>,
 }
+
+fn trim_optional(value: &mut Option<String>) {
+    *value = value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string);
+}
+
+impl Role {
+    /// 去除首尾空白，空字符串视为未填写。
+    pub(crate) fn normalize(&mut self) {
+        self.role_id = self.role_id.trim().to_string();
+        self.server = self.server.trim().to_string();
+        trim_optional(&mut self.name);
+        trim_optional(&mut self.school);
+        trim_optional(&mut self.body_type);
+        trim_optional(&mut self.notes);
+    }
+
+    /// 角色 ID 或区服未填写时视为空行，随账号批量保存时跳过。
+    pub(crate) fn is_blank(&self) -> bool {
+        self.role_id.trim().is_empty() || self.server.trim().is_empty()
+    }
+
+    pub(crate) fn validate(&self) -> AppResult<()> {
+        if self.is_blank() {
+            return Err(AppError::Validation("角色 ID 与区服不能为空".to_string()));
+        }
+        if self.level.is_some_and(|level| level < 1) {
+            return Err(AppError::Validation("角色等级必须大于 0".to_string()));
+        }
+        if self.equipment_score.is_some_and(|score| score < 0) {
+            return Err(AppError::Validation("装备分数不能为负数".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// 跨账号搜索角色的条件，未填写的条件不参与过滤。
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct RoleQuery {
+    /// 按角色 ID 或角色名模糊匹配。
+    pub(crate) keyword: Option<String>,
+    pub(crate) server: Option<String>,
+    pub(crate) zone: Option<String>,
+    pub(crate) school: Option<String>,
+}
+
+/// 角色搜索结果，附带所属账号与区服信息。
+#[derive(Debug, Serialize)]
+pub(crate) struct RoleSearchResult {
+    pub(crate) role: Role,
+    pub(crate) account_name: String,
+    /// 区服不在区服列表中时为 `None`。
+    pub(crate) zone: Option<String>,
+    pub(crate) status: Option<String>,
+}
@@ -0,0 +1,79 @@
+use super::role::Role;
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+
+/// 任务的刷新周期。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TaskPeriod {
+    /// 每日 07:00 刷新。
+    Daily,
+    /// 每周一 07:00 刷新。
+    Weekly,
+}
+
+impl TaskPeriod {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            TaskPeriod::Daily => "daily",
+            TaskPeriod::Weekly => "weekly",
+        }
+    }
+
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "daily" => Some(TaskPeriod::Daily),
+            "weekly" => Some(TaskPeriod::Weekly),
+            _ => None,
+        }
+    }
+}
+
+/// 任务模板，对所有角色生效，如“大战”“茶馆”“周常副本”。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RoleTask {
+    pub(crate) id: Option<i32>,
+    pub(crate) name: String,
+    pub(crate) period: TaskPeriod,
+    #[serde(default)]
+    pub(crate) description: Option<String>,
+    #[serde(default)]
+    pub(crate) sequence: Option<i32>,
+    /// 停用的任务不出现在待办列表中，已有的完成记录保留。
+    #[serde(default = "default_enabled")]
+    pub(crate) enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl RoleTask {
+    pub(crate) fn validate(&self) -> AppResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(AppError::Validation("任务名称不能为空".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// 某个角色在当前周期内的任务进度。
+#[derive(Debug, Serialize)]
+pub(crate) struct RoleTaskProgress {
+    pub(crate) task: RoleTask,
+    /// 本周期内的完成时间（Unix 时间戳，秒），未完成时为 `None`。
+    pub(crate) completed_at: Option<i64>,
+    /// 下次刷新时间（Unix 时间戳，秒）。
+    pub(crate) reset_at: i64,
+}
+
+/// 跨账号待办：某个角色在当前周期内尚未完成的任务。
+#[derive(Debug, Serialize)]
+pub(crate) struct OutstandingTask {
+    pub(crate) task_id: i32,
+    pub(crate) task_name: String,
+    pub(crate) period: TaskPeriod,
+    pub(crate) reset_at: i64,
+    pub(crate) role: Role,
+    pub(crate) account_name: String,
+}
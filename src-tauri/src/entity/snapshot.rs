@@ -0,0 +1,54 @@
+use serde::Serialize;
+
+/// 创建快照的时机，同时作为快照文件名的一部分。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SnapshotReason {
+    /// 执行数据库结构迁移前。
+    Migration,
+    /// 导入账号库、CSV 或恢复备份前。
+    Import,
+    /// 删除账号、分类前。
+    Delete,
+    /// 每日定时快照。
+    Daily,
+    /// 从快照恢复前，便于撤销这次恢复。
+    Restore,
+}
+
+impl SnapshotReason {
+    pub(crate) const ALL: [SnapshotReason; 5] = [
+        SnapshotReason::Migration,
+        SnapshotReason::Import,
+        SnapshotReason::Delete,
+        SnapshotReason::Daily,
+        SnapshotReason::Restore,
+    ];
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            SnapshotReason::Migration => "migration",
+            SnapshotReason::Import => "import",
+            SnapshotReason::Delete => "delete",
+            SnapshotReason::Daily => "daily",
+            SnapshotReason::Restore => "restore",
+        }
+    }
+
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|reason| reason.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct SnapshotInfo {
+    /// 快照文件名，恢复时用于指定快照。
+    pub(crate) name: String,
+    pub(crate) reason: SnapshotReason,
+    /// 创建时间（Unix 时间戳，秒）。
+    pub(crate) created_at: i64,
+    /// 文件大小（字节）。
+    pub(crate) size: u64,
+}
@@ -0,0 +1,118 @@
+use super::account::Account;
+use super::account_category::AccountCategory;
+use super::category::Category;
+use super::jx3_server_merge::Jx3ServerMerge;
+use super::ledger::LedgerEntry;
+use super::raid::RaidInstance;
+use super::role_task::RoleTask;
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 导出文件的格式标识，用于拒绝导入其他程序生成的 JSON。
+pub(crate) const VAULT_DOCUMENT_FORMAT: &str = "auto-paste-vault";
+/// 导出文件的结构版本。结构发生不兼容变化时递增，并在 `parse` 中补充旧版本的升级逻辑。
+pub(crate) const VAULT_DOCUMENT_VERSION: u32 = 1;
+
+/// 任务完成记录，`role_id`、`task_id` 为导出时的 id。
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct TaskRecordExport {
+    pub(crate) role_id: i32,
+    pub(crate) task_id: i32,
+    pub(crate) period_start: i64,
+    pub(crate) completed_at: i64,
+}
+
+/// 副本 CD 记录，`role_id`、`raid_id` 为导出时的 id。
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct RaidLockoutExport {
+    pub(crate) role_id: i32,
+    pub(crate) raid_id: i32,
+    pub(crate) locked_at: i64,
+    pub(crate) expires_at: i64,
+}
+
+/// 完整账号库的导出文档。
+///
+/// 账号的用户名、密码为明文，角色挂在 `accounts[].roles` 下；其余数据中的 id 均为导出时的 id，
+/// 导入时重新分配并按对应关系改写。
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct VaultDocument {
+    pub(crate) format: String,
+    pub(crate) version: u32,
+    pub(crate) app_version: String,
+    /// 导出时间（Unix 时间戳，秒）。
+    pub(crate) exported_at: i64,
+    pub(crate) accounts: Vec<Account>,
+    pub(crate) categories: Vec<Category>,
+    /// 账号与分类的关联，`group_id` 为分类 id。
+    pub(crate) account_categories: Vec<AccountCategory>,
+    /// 界面偏好等可迁移的设置，不含主密码校验信息与同步缓存。
+    #[serde(default)]
+    pub(crate) settings: BTreeMap<String, String>,
+    #[serde(default)]
+    pub(crate) role_tasks: Vec<RoleTask>,
+    #[serde(default)]
+    pub(crate) task_records: Vec<TaskRecordExport>,
+    #[serde(default)]
+    pub(crate) raids: Vec<RaidInstance>,
+    #[serde(default)]
+    pub(crate) raid_lockouts: Vec<RaidLockoutExport>,
+    #[serde(default)]
+    pub(crate) ledger: Vec<LedgerEntry>,
+    #[serde(default)]
+    pub(crate) server_merges: Vec<Jx3ServerMerge>,
+    #[serde(default)]
+    pub(crate) watched_servers: Vec<String>,
+}
+
+impl VaultDocument {
+    /// 解析导出文件，先校验格式标识与版本，再按当前结构反序列化。
+    pub(crate) fn parse(text: &str) -> AppResult<Self> {
+        let value: serde_json::Value = serde_json::from_str(text)?;
+        if value.get("format").and_then(|v| v.as_str()) != Some(VAULT_DOCUMENT_FORMAT) {
+            return Err(AppError::Validation("不是有效的账号库导出文件".to_string()));
+        }
+        let version = value
+            .get("version")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| AppError::Validation("导出文件缺少版本号".to_string()))?;
+        if version == 0 || version > u64::from(VAULT_DOCUMENT_VERSION) {
+            return Err(AppError::Validation(format!(
+                "不支持的导出文件版本 {version}（当前支持 1 - {VAULT_DOCUMENT_VERSION}），请升级应用后再导入"
+            )));
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    pub(crate) fn summary(&self) -> VaultTransferSummary {
+        VaultTransferSummary {
+            accounts: self.accounts.len(),
+            categories: self.categories.len(),
+            roles: self
+                .accounts
+                .iter()
+                .map(|account| account.roles.as_ref().map_or(0, Vec::len))
+                .sum(),
+            settings: self.settings.len(),
+            role_tasks: self.role_tasks.len(),
+            raids: self.raids.len(),
+            ledger_entries: self.ledger.len(),
+            skipped: 0,
+        }
+    }
+}
+
+/// 导出或导入的数据量。
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct VaultTransferSummary {
+    pub(crate) accounts: usize,
+    pub(crate) categories: usize,
+    pub(crate) roles: usize,
+    pub(crate) settings: usize,
+    pub(crate) role_tasks: usize,
+    pub(crate) raids: usize,
+    pub(crate) ledger_entries: usize,
+    /// 导入时因引用不到对应数据而跳过的记录数。
+    pub(crate) skipped: usize,
+}
@@ -0,0 +1,116 @@
+use crate::vault::VaultError;
+use serde::Serialize;
+use std::fmt;
+use std::io;
+
+/// 返回给前端的统一错误类型，序列化为 `{ code, message }`。
+///
+/// `code` 是稳定的错误码，前端据此区分处理；`message` 为可直接展示的中文说明。
+#[derive(Debug, Serialize)]
+#[serde(tag = "code", content = "message", rename_all = "snake_case")]
+pub(crate) enum AppError {
+    NotFound(String),
+    Validation(String),
+    Storage(String),
+    Network(String),
+    Locked(String),
+    Conflict(String),
+}
+
+pub(crate) type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub(crate) fn message(&self) -> &str {
+        match self {
+            AppError::NotFound(message)
+            | AppError::Validation(message)
+            | AppError::Storage(message)
+            | AppError::Network(message)
+            | AppError::Locked(message)
+            | AppError::Conflict(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<VaultError> for AppError {
+    fn from(err: VaultError) -> Self {
+        let message = err.to_string();
+        match err {
+            VaultError::Locked => AppError::Locked(message),
+            VaultError::NotInitialized => AppError::NotFound(message),
+            VaultError::AlreadyInitialized => AppError::Conflict(message),
+            VaultError::WrongPassword
+            | VaultError::WeakPassword
+            | VaultError::WeakPassphrase
+            | VaultError::WrongPassphrase => AppError::Validation(message),
+            VaultError::Crypto(_) => AppError::Storage(message),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(err: rusqlite::Error) -> Self {
+        use rusqlite::ErrorCode;
+
+        match err {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound("记录不存在".to_string()),
+            // 行映射闭包只能返回 rusqlite::Error，解密失败会被包装在转换错误里
+            rusqlite::Error::FromSqlConversionFailure(_, _, inner)
+            | rusqlite::Error::ToSqlConversionFailure(inner) => {
+                match inner.downcast::<VaultError>() {
+                    Ok(vault_err) => (*vault_err).into(),
+                    Err(inner) => AppError::Storage(format!("数据转换失败: {inner}")),
+                }
+            }
+            rusqlite::Error::SqliteFailure(ref failure, _) => match failure.code {
+                ErrorCode::ConstraintViolation => AppError::Conflict(format!("数据冲突: {err}")),
+                ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => {
+                    AppError::Locked("数据库正被占用，请稍后重试".to_string())
+                }
+                _ => AppError::Storage(format!("数据库错误: {err}")),
+            },
+            err => AppError::Storage(format!("数据库错误: {err}")),
+        }
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::InvalidInput => AppError::Validation(err.to_string()),
+            io::ErrorKind::NotFound => AppError::NotFound(format!("文件不存在: {err}")),
+            io::ErrorKind::AlreadyExists => AppError::Conflict(format!("文件已存在: {err}")),
+            _ => AppError::Storage(format!("文件操作失败: {err}")),
+        }
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(err: reqwest::Error) -> Self {
+        AppError::Network(format!("网络请求失败: {err}"))
+    }
+}
+
+impl From<csv::Error> for AppError {
+    fn from(err: csv::Error) -> Self {
+        if err.is_io_error() {
+            AppError::Storage(format!("文件操作失败: {err}"))
+        } else {
+            AppError::Validation(format!("CSV 格式错误: {err}"))
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        AppError::Validation(format!("数据格式错误: {err}"))
+    }
+}
@@ -0,0 +1,230 @@
+use crate::entity::jx3_server::Jx3Server;
+use crate::entity::jx3_server_merge::Jx3ServerMerge;
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// 随应用打包的兜底区服列表（相对资源目录）。
+pub(crate) const FALLBACK_RESOURCE: &str = "resources/jx3_servers.json";
+/// 用户自行维护的兜底区服列表，放在应用数据目录下。
+pub(crate) const FALLBACK_OVERRIDE_FILE_NAME: &str = "jx3_servers.json";
+
+/// 编译进程序的副本，资源文件缺失或损坏时使用；启动加载资源前的数据库迁移也依赖它。
+const BUILTIN_FALLBACK: &str = include_str!("../resources/jx3_servers.json");
+
+#[derive(Debug, Deserialize)]
+struct FallbackFile {
+    version: u32,
+    servers: Vec<FallbackServer>,
+    #[serde(default)]
+    merges: Vec<Jx3ServerMerge>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FallbackServer {
+    zone: String,
+    server: String,
+    status: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum FallbackSource {
+    Builtin,
+    Bundled,
+    Override,
+}
+
+struct FallbackList {
+    version: u32,
+    source: FallbackSource,
+    servers: Vec<Jx3Server>,
+    merges: Vec<Jx3ServerMerge>,
+}
+
+struct FallbackState {
+    list: FallbackList,
+    override_path: Option<PathBuf>,
+    override_error: Option<String>,
+}
+
+/// 当前使用的兜底区服列表信息。
+#[derive(Debug, Serialize)]
+pub(crate) struct FallbackInfo {
+    pub(crate) version: u32,
+    pub(crate) source: FallbackSource,
+    pub(crate) server_count: usize,
+    pub(crate) merge_count: usize,
+    pub(crate) override_path: Option<String>,
+    /// 用户文件存在但无法使用时的原因。
+    pub(crate) override_error: Option<String>,
+}
+
+lazy_static::lazy_static! {
+    static ref FALLBACK_STATE: RwLock<FallbackState> = RwLock::new(FallbackState {
+        list: parse_fallback(BUILTIN_FALLBACK, FallbackSource::Builtin)
+            .expect("内置兜底区服列表无效"),
+        override_path: None,
+        override_error: None,
+    });
+}
+
+fn parse_fallback(content: &str, source: FallbackSource) -> AppResult<FallbackList> {
+    let file: FallbackFile = serde_json::from_str(content)?;
+    if file.version == 0 {
+        return Err(AppError::Validation(
+            "兜底区服列表版本号必须大于 0".to_string(),
+        ));
+    }
+    if file.servers.is_empty() {
+        return Err(AppError::Validation("兜底区服列表为空".to_string()));
+    }
+
+    let mut seen = HashSet::new();
+    let mut servers = Vec::with_capacity(file.servers.len());
+    for item in file.servers {
+        let (zone, server, status) = (item.zone.trim(), item.server.trim(), item.status.trim());
+        if zone.is_empty() || server.is_empty() || status.is_empty() {
+            return Err(AppError::Validation(
+                "兜底区服的大区、区服、状态不能为空".to_string(),
+            ));
+        }
+        if !seen.insert(server.to_string()) {
+            return Err(AppError::Validation(format!("兜底区服重复: {server}")));
+        }
+        servers.push(Jx3Server {
+            id: None,
+            zone: zone.to_string(),
+            server: server.to_string(),
+            status: status.to_string(),
+            last_update_time: None,
+            watched: false,
+        });
+    }
+
+    let mut merges = file.merges;
+    for merge in &mut merges {
+        merge.validate()?;
+        merge.old_server = merge.old_server.trim().to_string();
+        merge.new_server = merge.new_server.trim().to_string();
+        merge.effective_date = merge.effective_date.trim().to_string();
+        merge.applied_at = None;
+    }
+
+    Ok(FallbackList {
+        version: file.version,
+        source,
+        servers,
+        merges,
+    })
+}
+
+fn read_fallback(path: &Path, source: FallbackSource) -> AppResult<FallbackList> {
+    parse_fallback(&fs::read_to_string(path)?, source)
+}
+
+/// 在内置副本、打包资源与用户文件中选择版本最高的一份，版本相同时优先用户文件；
+/// 另外返回用户文件未被采用的原因。
+fn select_fallback(
+    resource_path: Option<&Path>,
+    override_path: &Path,
+) -> (FallbackList, Option<String>) {
+    let mut list =
+        parse_fallback(BUILTIN_FALLBACK, FallbackSource::Builtin).expect("内置兜底区服列表无效");
+
+    if let Some(path) = resource_path {
+        match read_fallback(path, FallbackSource::Bundled) {
+            Ok(bundled) if bundled.version >= list.version => list = bundled,
+            Ok(_) => {}
+            Err(e) => println!("加载打包的兜底区服列表失败: {e}"),
+        }
+    }
+
+    let mut override_error = None;
+    if override_path.exists() {
+        match read_fallback(override_path, FallbackSource::Override) {
+            Ok(user) if user.version >= list.version => list = user,
+            Ok(user) => {
+                let message = format!(
+                    "用户兜底区服列表版本({})低于内置版本({})，已忽略",
+                    user.version, list.version
+                );
+                println!("{message}");
+                override_error = Some(message);
+            }
+            Err(e) => {
+                println!("加载用户兜底区服列表失败: {e}");
+                override_error = Some(e.to_string());
+            }
+        }
+    }
+
+    (list, override_error)
+}
+
+/// 启动时加载兜底区服列表，选择规则见 `select_fallback`。
+pub(crate) fn load(resource_path: Option<&Path>, override_path: &Path) {
+    let (list, override_error) = select_fallback(resource_path, override_path);
+    *FALLBACK_STATE.write().unwrap() = FallbackState {
+        list,
+        override_path: Some(override_path.to_path_buf()),
+        override_error,
+    };
+}
+
+pub(crate) fn servers() -> Vec<Jx3Server> {
+    FALLBACK_STATE.read().unwrap().list.servers.clone()
+}
+
+/// 兜底列表附带的合服记录。
+pub(crate) fn merges() -> Vec<Jx3ServerMerge> {
+    FALLBACK_STATE.read().unwrap().list.merges.clone()
+}
+
+pub(crate) fn version() -> u32 {
+    FALLBACK_STATE.read().unwrap().list.version
+}
+
+pub(crate) fn info() -> FallbackInfo {
+    let state = FALLBACK_STATE.read().unwrap();
+    FallbackInfo {
+        version: state.list.version,
+        source: state.list.source,
+        server_count: state.list.servers.len(),
+        merge_count: state.list.merges.len(),
+        override_path: state
+            .override_path
+            .as_ref()
+            .map(|path| path.to_string_lossy().into_owned()),
+        override_error: state.override_error.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outdated_override_is_reported() {
+        let dir =
+            std::env::temp_dir().join(format!("auto-paste-test-{}-fallback", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let list = |version: u32| {
+            format!(
+                r#"{{"version":{version},"servers":[{{"zone":"电信区","server":"测试服","status":"正常"}}]}}"#
+            )
+        };
+        let bundled = dir.join("bundled.json");
+        let user = dir.join("user.json");
+        fs::write(&bundled, list(1000)).unwrap();
+        fs::write(&user, list(999)).unwrap();
+
+        let (list, override_error) = select_fallback(Some(&bundled), &user);
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!((list.version, list.source), (1000, FallbackSource::Bundled));
+        assert!(override_error.is_some_and(|error| error.contains("低于内置版本")));
+    }
+}
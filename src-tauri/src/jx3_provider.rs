@@ -0,0 +1,641 @@
+use crate::entity::jx3_server::Jx3Server;
+use crate::error::{AppError, AppResult};
+use reqwest::header::{
+    HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER,
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::time::{Duration, SystemTime};
+use tokio::time::sleep;
+
+const JX3_API_URL: &str = "https://www.jx3api.com/server/status/check";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub(crate) type ProviderFuture<'a> =
+    Pin<Box<dyn Future<Output = AppResult<FetchOutcome>> + Send + 'a>>;
+
+/// 条件请求所需的缓存校验信息，按请求地址保存在 `app_setting` 中。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CacheValidators {
+    pub(crate) url: String,
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    fn from_headers(url: &str, headers: &HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        CacheValidators {
+            url: url.to_string(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum FetchOutcome {
+    /// 服务器返回 304，数据与上次相同。
+    NotModified,
+    /// 获取到新数据；`validators` 为 `None` 表示该来源不支持条件请求。
+    Fetched {
+        servers: Vec<Jx3Server>,
+        validators: Option<CacheValidators>,
+    },
+}
+
+/// 区服状态数据来源。
+///
+/// 返回装箱的 future 以便按设置在运行时选择实现（`Box<dyn ServerStatusProvider>`）。
+/// `cache` 为上次成功获取时保存的校验信息，地址不匹配时应忽略。
+pub(crate) trait ServerStatusProvider: Send + Sync {
+    fn fetch<'a>(&'a self, cache: Option<&'a CacheValidators>) -> ProviderFuture<'a>;
+}
+
+/// 响应中区服字段的位置。`list_path` 为以 `.` 分隔的路径，指向区服数组（或单个区服对象），留空表示根节点。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct FieldMapping {
+    pub(crate) list_path: String,
+    pub(crate) zone: String,
+    pub(crate) server: String,
+    pub(crate) status: String,
+}
+
+impl Default for FieldMapping {
+    fn default() -> Self {
+        FieldMapping {
+            list_path: String::new(),
+            zone: "zone".to_string(),
+            server: "server".to_string(),
+            status: "status".to_string(),
+        }
+    }
+}
+
+impl FieldMapping {
+    fn validate(&self) -> AppResult<()> {
+        if [&self.zone, &self.server, &self.status]
+            .iter()
+            .any(|field| field.trim().is_empty())
+        {
+            return Err(AppError::Validation(
+                "大区、区服、状态字段名不能为空".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn resolve<'a>(&self, root: &'a Value) -> Option<&'a Value> {
+        self.list_path
+            .split('.')
+            .map(str::trim)
+            .filter(|segment| !segment.is_empty())
+            .try_fold(root, |value, segment| match value {
+                Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+                _ => value.get(segment),
+            })
+    }
+
+    fn parse_item(&self, item: &Value) -> Option<Jx3Server> {
+        let field = |name: &str| item.get(name)?.as_str().map(str::to_string);
+        Some(Jx3Server {
+            id: None,
+            zone: field(&self.zone)?,
+            server: field(&self.server)?,
+            status: field(&self.status)?,
+            last_update_time: None,
+            watched: false,
+        })
+    }
+
+    /// 按映射解析区服列表，无法解析的条目会被跳过；一条都解析不出时返回错误。
+    pub(crate) fn parse(&self, root: &Value) -> AppResult<Vec<Jx3Server>> {
+        let servers: Vec<Jx3Server> = match self.resolve(root) {
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|item| self.parse_item(item))
+                .collect(),
+            Some(item @ Value::Object(_)) => self.parse_item(item).into_iter().collect(),
+            _ => Vec::new(),
+        };
+
+        if servers.is_empty() {
+            return Err(AppError::Network("无法解析区服数据".to_string()));
+        }
+        Ok(servers)
+    }
+}
+
+/// 请求失败时的重试策略。
+#[derive(Debug, Clone)]
+pub(crate) struct RetryPolicy {
+    /// 包括首次请求在内的最多尝试次数。
+    pub(crate) max_attempts: u32,
+    /// 第 n 次重试前等待 `base_delay * 2^(n-1)`，另加不超过该值的随机抖动。
+    pub(crate) base_delay: Duration,
+    /// 服务器要求等待（Retry-After）超过该值时不再重试，直接返回错误。
+    pub(crate) max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn backoff(&self, retry: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << retry.saturating_sub(1).min(8));
+        delay + jitter(delay)
+    }
+}
+
+/// 返回 `[0, max)` 内的随机时长，避免多个客户端同时重试。
+fn jitter(max: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    max.mul_f64((random % 1000) as f64 / 1000.0)
+}
+
+/// 解析 Retry-After：秒数或 HTTP 日期。
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+fn http_client() -> AppResult<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()?)
+}
+
+enum HttpOutcome {
+    NotModified,
+    Fetched {
+        body: Value,
+        validators: CacheValidators,
+    },
+}
+
+/// 发送 GET 请求：带上缓存校验信息，遇到连接错误、429 与 5xx 时按策略重试。
+async fn get_json(
+    url: &str,
+    cache: Option<&CacheValidators>,
+    policy: &RetryPolicy,
+) -> AppResult<HttpOutcome> {
+    let client = http_client()?;
+    let cache = cache.filter(|cache| cache.url == url);
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        let mut request = client.get(url);
+        if let Some(cache) = cache {
+            if let Some(etag) = &cache.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cache.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let retry_after = match request.send().await {
+            Ok(response) => {
+                let status = response.status();
+                if status == StatusCode::NOT_MODIFIED {
+                    return Ok(HttpOutcome::NotModified);
+                }
+                if status.is_success() {
+                    let validators = CacheValidators::from_headers(url, response.headers());
+                    let body: Value = response.json().await?;
+                    return Ok(HttpOutcome::Fetched { body, validators });
+                }
+
+                let retryable = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                let error = if status == StatusCode::TOO_MANY_REQUESTS {
+                    AppError::Network("请求过于频繁，已被限流".to_string())
+                } else {
+                    AppError::Network(format!("请求失败: HTTP {}", status.as_u16()))
+                };
+                if !retryable || attempt >= policy.max_attempts {
+                    return Err(error);
+                }
+                let retry_after = parse_retry_after(response.headers());
+                if let Some(wait) = retry_after.filter(|wait| *wait > policy.max_retry_after) {
+                    return Err(AppError::Network(format!(
+                        "{}，请在 {} 秒后重试",
+                        error.message(),
+                        wait.as_secs()
+                    )));
+                }
+                retry_after
+            }
+            Err(err) => {
+                if attempt >= policy.max_attempts || !(err.is_connect() || err.is_timeout()) {
+                    return Err(err.into());
+                }
+                None
+            }
+        };
+
+        sleep(retry_after.unwrap_or_else(|| policy.backoff(attempt))).await;
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Jx3ApiResponse {
+    code: i32,
+    data: Value,
+}
+
+/// 默认数据来源：JX3API 公开接口。
+pub(crate) struct Jx3ApiProvider {
+    url: String,
+    policy: RetryPolicy,
+}
+
+impl ServerStatusProvider for Jx3ApiProvider {
+    fn fetch<'a>(&'a self, cache: Option<&'a CacheValidators>) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let (body, validators) = match get_json(&self.url, cache, &self.policy).await? {
+                HttpOutcome::NotModified => return Ok(FetchOutcome::NotModified),
+                HttpOutcome::Fetched { body, validators } => (body, validators),
+            };
+            let body: Jx3ApiResponse = serde_json::from_value(body)?;
+            if body.code != 200 {
+                return Err(AppError::Network(format!(
+                    "JX3API 返回错误: code={}",
+                    body.code
+                )));
+            }
+            Ok(FetchOutcome::Fetched {
+                servers: FieldMapping::default().parse(&body.data)?,
+                validators: Some(validators).filter(|v| !v.is_empty()),
+            })
+        })
+    }
+}
+
+/// 任意返回 JSON 的接口（如镜像站），按字段映射解析。
+pub(crate) struct JsonEndpointProvider {
+    url: String,
+    mapping: FieldMapping,
+    policy: RetryPolicy,
+}
+
+impl ServerStatusProvider for JsonEndpointProvider {
+    fn fetch<'a>(&'a self, cache: Option<&'a CacheValidators>) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            match get_json(&self.url, cache, &self.policy).await? {
+                HttpOutcome::NotModified => Ok(FetchOutcome::NotModified),
+                HttpOutcome::Fetched { body, validators } => Ok(FetchOutcome::Fetched {
+                    servers: self.mapping.parse(&body)?,
+                    validators: Some(validators).filter(|v| !v.is_empty()),
+                }),
+            }
+        })
+    }
+}
+
+/// 本地 JSON 文件，用于公开接口不可用时手动维护区服状态。
+pub(crate) struct LocalFileProvider {
+    path: PathBuf,
+    mapping: FieldMapping,
+}
+
+impl ServerStatusProvider for LocalFileProvider {
+    fn fetch<'a>(&'a self, _cache: Option<&'a CacheValidators>) -> ProviderFuture<'a> {
+        Box::pin(async move {
+            let content = std::fs::read_to_string(&self.path)?;
+            let body: Value = serde_json::from_str(&content)?;
+            Ok(FetchOutcome::Fetched {
+                servers: self.mapping.parse(&body)?,
+                validators: None,
+            })
+        })
+    }
+}
+
+/// 数据来源设置，以 JSON 保存在 `app_setting` 中。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum ProviderConfig {
+    #[default]
+    Jx3Api,
+    JsonEndpoint {
+        url: String,
+        #[serde(default)]
+        mapping: FieldMapping,
+    },
+    LocalFile {
+        path: String,
+        #[serde(default)]
+        mapping: FieldMapping,
+    },
+}
+
+impl ProviderConfig {
+    pub(crate) fn validate(&self) -> AppResult<()> {
+        match self {
+            ProviderConfig::Jx3Api => Ok(()),
+            ProviderConfig::JsonEndpoint { url, mapping } => {
+                let url = url.trim();
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    return Err(AppError::Validation(
+                        "接口地址必须以 http:// 或 https:// 开头".to_string(),
+                    ));
+                }
+                mapping.validate()
+            }
+            ProviderConfig::LocalFile { path, mapping } => {
+                if path.trim().is_empty() {
+                    return Err(AppError::Validation("请选择本地文件".to_string()));
+                }
+                mapping.validate()
+            }
+        }
+    }
+
+    pub(crate) fn build(self) -> Box<dyn ServerStatusProvider> {
+        match self {
+            ProviderConfig::Jx3Api => Box::new(Jx3ApiProvider {
+                url: JX3_API_URL.to_string(),
+                policy: RetryPolicy::default(),
+            }),
+            ProviderConfig::JsonEndpoint { url, mapping } => Box::new(JsonEndpointProvider {
+                url: url.trim().to_string(),
+                mapping,
+                policy: RetryPolicy::default(),
+            }),
+            ProviderConfig::LocalFile { path, mapping } => Box::new(LocalFileProvider {
+                path: PathBuf::from(path.trim()),
+                mapping,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Instant;
+
+    const BODY: &str = r#"[{"zone":"电信区","server":"梦江南","status":"正常"}]"#;
+
+    /// 按顺序返回预设响应的本地 HTTP 服务，记录收到的每个请求头（小写）。
+    struct MockServer {
+        url: String,
+        requests: Arc<Mutex<Vec<Vec<String>>>>,
+    }
+
+    impl MockServer {
+        fn start(responses: Vec<String>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/servers", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let recorded = Arc::clone(&requests);
+            thread::spawn(move || {
+                for response in responses {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut headers = Vec::new();
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        let line = line.trim_end();
+                        if line.is_empty() {
+                            break;
+                        }
+                        headers.push(line.to_lowercase());
+                    }
+                    recorded.lock().unwrap().push(headers);
+                    stream.write_all(response.as_bytes()).unwrap();
+                }
+            });
+            MockServer { url, requests }
+        }
+
+        fn requests(&self) -> Vec<Vec<String>> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    fn response(status: &str, headers: &[&str], body: &str) -> String {
+        let mut text = format!(
+            "HTTP/1.1 {status}\r\nConnection: close\r\nContent-Length: {}\r\n",
+            body.len()
+        );
+        for header in headers {
+            text.push_str(header);
+            text.push_str("\r\n");
+        }
+        text.push_str("\r\n");
+        text.push_str(body);
+        text
+    }
+
+    fn provider(url: &str) -> JsonEndpointProvider {
+        JsonEndpointProvider {
+            url: url.to_string(),
+            mapping: FieldMapping::default(),
+            policy: RetryPolicy {
+                max_attempts: 3,
+                base_delay: Duration::from_millis(10),
+                max_retry_after: Duration::from_secs(5),
+            },
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn has_header(request: &[String], header: &str) -> bool {
+        request.iter().any(|line| line == header)
+    }
+
+    #[test]
+    fn conditional_request_returns_not_modified() {
+        let server = MockServer::start(vec![
+            response(
+                "200 OK",
+                &[
+                    "ETag: \"v1\"",
+                    "Last-Modified: Wed, 21 Oct 2015 07:28:00 GMT",
+                ],
+                BODY,
+            ),
+            response("304 Not Modified", &[], ""),
+        ]);
+        let provider = provider(&server.url);
+
+        let validators = match block_on(provider.fetch(None)).unwrap() {
+            FetchOutcome::Fetched {
+                servers,
+                validators,
+            } => {
+                assert_eq!(servers.len(), 1);
+                assert_eq!(servers[0].server, "梦江南");
+                validators.unwrap()
+            }
+            FetchOutcome::NotModified => panic!("首次请求不应返回 304"),
+        };
+        assert_eq!(validators.url, server.url);
+        assert_eq!(validators.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(
+            validators.last_modified.as_deref(),
+            Some("Wed, 21 Oct 2015 07:28:00 GMT")
+        );
+
+        let outcome = block_on(provider.fetch(Some(&validators))).unwrap();
+        assert!(matches!(outcome, FetchOutcome::NotModified));
+
+        let requests = server.requests();
+        assert!(!requests[0]
+            .iter()
+            .any(|line| line.starts_with("if-none-match")));
+        assert!(has_header(&requests[1], "if-none-match: \"v1\""));
+        assert!(has_header(
+            &requests[1],
+            "if-modified-since: wed, 21 oct 2015 07:28:00 gmt"
+        ));
+    }
+
+    #[test]
+    fn validators_for_other_url_are_ignored() {
+        let server = MockServer::start(vec![response("200 OK", &[], BODY)]);
+        let cache = CacheValidators {
+            url: "http://example.invalid/other".to_string(),
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+        };
+
+        let outcome = block_on(provider(&server.url).fetch(Some(&cache))).unwrap();
+        assert!(matches!(
+            outcome,
+            FetchOutcome::Fetched {
+                validators: None,
+                ..
+            }
+        ));
+        assert!(!server.requests()[0]
+            .iter()
+            .any(|line| line.starts_with("if-none-match")));
+    }
+
+    #[test]
+    fn rate_limit_honours_retry_after() {
+        let server = MockServer::start(vec![
+            response("429 Too Many Requests", &["Retry-After: 1"], ""),
+            response("200 OK", &[], BODY),
+        ]);
+
+        let started = Instant::now();
+        let outcome = block_on(provider(&server.url).fetch(None)).unwrap();
+        assert!(matches!(outcome, FetchOutcome::Fetched { .. }));
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn retry_after_beyond_limit_fails_immediately() {
+        let server = MockServer::start(vec![response(
+            "503 Service Unavailable",
+            &["Retry-After: 120"],
+            "",
+        )]);
+
+        let err = block_on(provider(&server.url).fetch(None)).unwrap_err();
+        assert!(matches!(err, AppError::Network(_)));
+        assert!(err.message().contains("120"));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn server_errors_retry_until_exhausted() {
+        let server = MockServer::start(vec![
+            response("500 Internal Server Error", &[], ""),
+            response("502 Bad Gateway", &[], ""),
+            response("500 Internal Server Error", &[], ""),
+        ]);
+
+        let err = block_on(provider(&server.url).fetch(None)).unwrap_err();
+        assert_eq!(err.message(), "请求失败: HTTP 500");
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[test]
+    fn server_error_then_success() {
+        let server = MockServer::start(vec![
+            response("502 Bad Gateway", &[], ""),
+            response("200 OK", &["ETag: \"v2\""], BODY),
+        ]);
+
+        match block_on(provider(&server.url).fetch(None)).unwrap() {
+            FetchOutcome::Fetched { validators, .. } => {
+                assert_eq!(validators.unwrap().etag.as_deref(), Some("\"v2\""));
+            }
+            FetchOutcome::NotModified => panic!("不应返回 304"),
+        }
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn client_errors_are_not_retried() {
+        let server = MockServer::start(vec![response("404 Not Found", &[], "")]);
+
+        let err = block_on(provider(&server.url).fetch(None)).unwrap_err();
+        assert_eq!(err.message(), "请求失败: HTTP 404");
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn retry_after_accepts_http_date() {
+        let mut headers = HeaderMap::new();
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(30));
+        headers.insert(RETRY_AFTER, date.parse().unwrap());
+        let wait = parse_retry_after(&headers).unwrap();
+        assert!(wait > Duration::from_secs(25) && wait <= Duration::from_secs(30));
+
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+    }
+}
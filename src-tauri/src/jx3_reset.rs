@@ -0,0 +1,120 @@
+use serde::Serialize;
+
+/// 游戏按北京时间（UTC+8）刷新。
+const RESET_UTC_OFFSET_SECS: i64 = 8 * 60 * 60;
+/// 每日 07:00 刷新日常，周一 07:00 同时刷新周常。
+const RESET_HOUR: i64 = 7;
+const DAY_SECS: i64 = 24 * 60 * 60;
+const WEEK_DAYS: i64 = 7;
+/// 1970-01-01 是周四，距周一 3 天。
+const EPOCH_WEEKDAY_FROM_MONDAY: i64 = 3;
+
+/// 把时间平移到“游戏日”坐标：刷新时刻对应当天 00:00。
+fn game_day(now: i64) -> i64 {
+    (now + RESET_UTC_OFFSET_SECS - RESET_HOUR * 60 * 60).div_euclid(DAY_SECS)
+}
+
+fn game_day_start(day: i64) -> i64 {
+    day * DAY_SECS - RESET_UTC_OFFSET_SECS + RESET_HOUR * 60 * 60
+}
+
+/// `now` 所在日常周期的开始时间（最近一次每日刷新）。
+pub(crate) fn daily_period_start(now: i64) -> i64 {
+    game_day_start(game_day(now))
+}
+
+/// `now` 所在周常周期的开始时间（最近一次周一刷新）。
+pub(crate) fn weekly_period_start(now: i64) -> i64 {
+    let day = game_day(now);
+    game_day_start(day - (day + EPOCH_WEEKDAY_FROM_MONDAY).rem_euclid(WEEK_DAYS))
+}
+
+/// `time` 之后的第一次周常刷新时间，即在 `time` 获得的副本 CD 的解除时间。
+pub(crate) fn next_weekly_reset(time: i64) -> i64 {
+    weekly_period_start(time) + WEEK_DAYS * DAY_SECS
+}
+
+/// 当前日常、周常周期及下次刷新时间（Unix 秒）。
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ResetSchedule {
+    pub(crate) daily_start: i64,
+    pub(crate) next_daily_reset: i64,
+    pub(crate) weekly_start: i64,
+    pub(crate) next_weekly_reset: i64,
+}
+
+impl ResetSchedule {
+    pub(crate) fn at(now: i64) -> Self {
+        let daily_start = daily_period_start(now);
+        let weekly_start = weekly_period_start(now);
+        ResetSchedule {
+            daily_start,
+            next_daily_reset: daily_start + DAY_SECS,
+            weekly_start,
+            next_weekly_reset: next_weekly_reset(now),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-01-01（周一）07:00 北京时间。
+    const MONDAY_RESET: i64 = 1_704_063_600;
+    const HOUR: i64 = 60 * 60;
+    const WEEK: i64 = WEEK_DAYS * DAY_SECS;
+
+    #[test]
+    fn daily_period_turns_over_at_seven() {
+        let tuesday_reset = MONDAY_RESET + DAY_SECS;
+        assert_eq!(daily_period_start(tuesday_reset - 1), MONDAY_RESET);
+        assert_eq!(daily_period_start(tuesday_reset), tuesday_reset);
+        assert_eq!(daily_period_start(tuesday_reset + 1), tuesday_reset);
+    }
+
+    #[test]
+    fn weekly_period_runs_from_monday_to_monday() {
+        let sunday_night = MONDAY_RESET + 6 * DAY_SECS + 16 * HOUR;
+        assert_eq!(weekly_period_start(MONDAY_RESET), MONDAY_RESET);
+        assert_eq!(weekly_period_start(sunday_night), MONDAY_RESET);
+        assert_eq!(
+            daily_period_start(sunday_night),
+            MONDAY_RESET + 6 * DAY_SECS
+        );
+    }
+
+    #[test]
+    fn monday_before_seven_belongs_to_previous_week() {
+        let next_monday = MONDAY_RESET + WEEK;
+        let monday_0659 = next_monday - 60;
+        assert_eq!(weekly_period_start(monday_0659), MONDAY_RESET);
+        assert_eq!(daily_period_start(monday_0659), next_monday - DAY_SECS);
+        assert_eq!(weekly_period_start(next_monday), next_monday);
+        assert_eq!(daily_period_start(next_monday), next_monday);
+    }
+
+    #[test]
+    fn next_weekly_reset_is_the_following_monday_seven() {
+        assert_eq!(next_weekly_reset(MONDAY_RESET - 60), MONDAY_RESET);
+        assert_eq!(next_weekly_reset(MONDAY_RESET), MONDAY_RESET + WEEK);
+        assert_eq!(
+            next_weekly_reset(MONDAY_RESET + 3 * DAY_SECS),
+            MONDAY_RESET + WEEK
+        );
+        assert_eq!(
+            next_weekly_reset(MONDAY_RESET + WEEK - 1),
+            MONDAY_RESET + WEEK
+        );
+    }
+
+    #[test]
+    fn schedule_combines_daily_and_weekly_periods() {
+        let now = MONDAY_RESET + 2 * DAY_SECS + 5 * HOUR;
+        let schedule = ResetSchedule::at(now);
+        assert_eq!(schedule.daily_start, MONDAY_RESET + 2 * DAY_SECS);
+        assert_eq!(schedule.next_daily_reset, MONDAY_RESET + 3 * DAY_SECS);
+        assert_eq!(schedule.weekly_start, MONDAY_RESET);
+        assert_eq!(schedule.next_weekly_reset, MONDAY_RESET + WEEK);
+    }
+}
@@ -1,94 +1,369 @@
-use crate::entity::jx3_server::Jx3Server;
-use serde::Deserialize;
-
-const JX3_API_URL: &str = "https://www.jx3api.com/server/status/check";
-
-pub(crate) const FALLBACK_JX3_SERVERS: &[(&str, &str, &str)] = &[
-    ("无界区", "眉间雪", "拥挤"),
-    ("无界区", "山海相逢", "正常"),
-    ("电信区", "龙争虎斗", "拥挤"),
-    ("电信区", "剑胆琴心", "拥挤"),
-    ("电信区", "斗转星移", "拥挤"),
-    ("电信区", "乾坤一掷", "爆满"),
-    ("电信区", "绝代天骄", "爆满"),
-    ("电信区", "梦江南", "爆满"),
-    ("电信区", "幽月轮", "拥挤"),
-    ("电信区", "长安城", "拥挤"),
-    ("电信区", "唯我独尊", "爆满"),
-    ("电信区", "蝶恋花", "拥挤"),
-    ("双线区", "天鹅坪", "爆满"),
-    ("双线区", "破阵子", "拥挤"),
-    ("双线区", "飞龙在天", "拥挤"),
-];
-
-#[derive(Debug, Deserialize)]
-struct ApiResponse {
-    code: i32,
-    data: serde_json::Value,
-}
-
-#[derive(Debug, Deserialize)]
-struct ServerItem {
-    zone: String,
-    server: String,
-    status: String,
-}
-
-pub(crate) fn fallback_servers() -> Vec<Jx3Server> {
-    FALLBACK_JX3_SERVERS
-        .iter()
-        .map(|(zone, server, status)| Jx3Server {
-            id: None,
-            zone: zone.to_string(),
-            server: server.to_string(),
-            status: status.to_string(),
-            last_update_time: None,
+use crate::db::Database;
+use crate::entity::jx3_server::{Jx3Server, Jx3ServerChange};
+use crate::error::{AppError, AppResult};
+use crate::jx3_fallback;
+use crate::jx3_provider::FetchOutcome;
+use crate::sqlite;
+use serde::Serialize;
+use std::future::{poll_fn, Future};
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
+use std::time::Duration;
+use tauri::api::notification::Notification;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+use tokio::time::sleep;
+
+pub(crate) const SYNC_PROGRESS_EVENT: &str = "jx3-sync-progress";
+pub(crate) const SYNC_FINISHED_EVENT: &str = "jx3-sync-finished";
+/// 区服数据有变化时发送，负载为发生变化的区服列表。
+pub(crate) const SERVERS_CHANGED_EVENT: &str = "jx3-servers-changed";
+/// 关注的区服状态变化时发送，负载为 `Jx3ServerChange`。
+pub(crate) const WATCHED_SERVER_CHANGED_EVENT: &str = "jx3-watched-server-changed";
+
+/// 连续失败时的最长重试间隔。
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+const MAX_BACKOFF_SHIFT: u32 = 6;
+
+/// 按设置的数据来源获取区服状态，带上次保存的缓存校验信息发送条件请求。
+async fn fetch_servers(db: &Database) -> AppResult<FetchOutcome> {
+    let provider = sqlite::get_jx3_provider_config(db)?.build();
+    let cache = sqlite::get_jx3_http_cache(db)?;
+    provider.fetch(cache.as_ref()).await
+}
+
+/// 写入联网获取的数据并保存缓存校验信息，数据未变化时只记录同步时间。
+fn save_fetched(app: &AppHandle, db: &Database, outcome: FetchOutcome) -> AppResult<SyncOutcome> {
+    match outcome {
+        FetchOutcome::NotModified => {
+            sqlite::touch_jx3_server_last_sync(db)?;
+            Ok(SyncOutcome::NotModified)
+        }
+        FetchOutcome::Fetched {
+            servers,
+            validators,
+        } => {
+            let changed = sqlite::save_jx3_servers(db, &servers, true, validators.as_ref())?;
+            emit_changed(app, changed, true);
+            Ok(SyncOutcome::Updated(servers.len()))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SyncStage {
+    Fetching,
+    Saving,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SyncProgress {
+    stage: SyncStage,
+    total: usize,
+}
+
+/// 同步结果的类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SyncResultKind {
+    /// 服务器返回 304，区服数据与上次相同。
+    NotModified,
+    Updated,
+    /// 未开启联网同步或联网获取失败，写入了兜底数据。
+    FallbackUsed,
+    Failed,
+    Cancelled,
+}
+
+/// 一次同步的结果，同时作为 `jx3-sync-finished` 事件的负载。
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct SyncSummary {
+    pub(crate) result: SyncResultKind,
+    pub(crate) updated: usize,
+    /// 失败原因；联网获取失败后改用兜底数据时也会带上。
+    pub(crate) error: Option<String>,
+}
+
+impl SyncSummary {
+    fn new(result: SyncResultKind, updated: usize, error: Option<String>) -> Self {
+        SyncSummary {
+            result,
+            updated,
+            error,
+        }
+    }
+}
+
+enum SyncOutcome {
+    NotModified,
+    Updated(usize),
+}
+
+/// 同步任务的运行状态，同一时间只允许一个同步任务（手动同步与后台刷新共用）。
+#[derive(Default)]
+pub(crate) struct Jx3SyncState {
+    running: Mutex<Option<Arc<Notify>>>,
+    /// 刷新相关设置变化时唤醒后台任务重新计算下次刷新时间。
+    settings_changed: Notify,
+}
+
+impl Jx3SyncState {
+    /// 通知后台刷新任务重新读取联网同步开关与刷新间隔。
+    pub(crate) fn reschedule(&self) {
+        self.settings_changed.notify_one();
+    }
+
+    fn begin(&self) -> AppResult<SyncGuard<'_>> {
+        let mut running = self.running.lock().unwrap();
+        if running.is_some() {
+            return Err(AppError::Conflict("区服数据正在同步中".to_string()));
+        }
+        let cancel = Arc::new(Notify::new());
+        *running = Some(Arc::clone(&cancel));
+        Ok(SyncGuard {
+            state: self,
+            cancel,
         })
-        .collect()
+    }
+
+    /// 请求取消正在进行的同步，返回是否存在进行中的同步。
+    pub(crate) fn cancel(&self) -> bool {
+        match self.running.lock().unwrap().as_ref() {
+            Some(cancel) => {
+                // notify_one 会保留许可，取消请求早于等待也不会丢失
+                cancel.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
 }
 
-fn parse_server_list(data: &serde_json::Value) -> Option<Vec<Jx3Server>> {
-    let items = if data.is_array() {
-        data.as_array()?.clone()
-    } else if data.is_object() && data.get("server").is_some() {
-        vec![data.clone()]
-    } else {
-        return None;
-    };
+/// 同步结束（包括出错或被取消）时清除运行状态。
+struct SyncGuard<'a> {
+    state: &'a Jx3SyncState,
+    cancel: Arc<Notify>,
+}
 
-    let mut servers = Vec::new();
-    for item in items {
-        if let Ok(parsed) = serde_json::from_value::<ServerItem>(item) {
-            servers.push(Jx3Server {
-                id: None,
-                zone: parsed.zone,
-                server: parsed.server,
-                status: parsed.status,
-                last_update_time: None,
-            });
+impl Drop for SyncGuard<'_> {
+    fn drop(&mut self) {
+        *self.state.running.lock().unwrap() = None;
+    }
+}
+
+/// 等待 `future` 完成，期间收到取消通知则返回 `None`，未完成的请求随之丢弃。
+async fn cancellable<F: Future>(future: F, cancel: &Notify) -> Option<F::Output> {
+    let mut future = pin!(future);
+    let mut cancelled = pin!(cancel.notified());
+    poll_fn(|cx| {
+        if cancelled.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(None);
         }
+        future.as_mut().poll(cx).map(Some)
+    })
+    .await
+}
+
+fn emit<S: Serialize + Clone>(app: &AppHandle, event: &str, payload: S) {
+    if let Err(e) = app.emit_all(event, payload) {
+        println!("emit {event} error: {:?}", e);
+    }
+}
+
+/// 推送发生变化的区服；`observed` 为 `true`（联网获取的真实状态）且关注的区服状态变化时，
+/// 另外发送事件并弹出系统通知。兜底数据不是实际观测到的状态，不触发通知。
+fn emit_changed(app: &AppHandle, changed: Vec<Jx3ServerChange>, observed: bool) {
+    if changed.is_empty() {
+        return;
     }
 
-    if servers.is_empty() {
-        None
-    } else {
-        Some(servers)
+    for change in changed
+        .iter()
+        .filter(|change| observed && change.is_watched_transition())
+    {
+        emit(app, WATCHED_SERVER_CHANGED_EVENT, change.clone());
+        notify_watched_change(app, change);
     }
+
+    let servers: Vec<Jx3Server> = changed.into_iter().map(|change| change.current).collect();
+    emit(app, SERVERS_CHANGED_EVENT, servers);
 }
 
-pub(crate) fn fetch_servers_from_api() -> Result<Vec<Jx3Server>, String> {
-    let response = reqwest::blocking::Client::builder()
-        .timeout(std::time::Duration::from_secs(10))
-        .build()
-        .map_err(|e| e.to_string())?
-        .get(JX3_API_URL)
-        .send()
-        .map_err(|e| e.to_string())?;
+fn notify_watched_change(app: &AppHandle, change: &Jx3ServerChange) {
+    let server = &change.current;
+    let previous = change.previous_status.as_deref().unwrap_or_default();
+    let result = Notification::new(&app.config().tauri.bundle.identifier)
+        .title(format!("{} 状态变化", server.server))
+        .body(format!(
+            "{}·{}：{} → {}",
+            server.zone, server.server, previous, server.status
+        ))
+        .show();
+    if let Err(e) = result {
+        println!("show notification error: {:?}", e);
+    }
+}
 
-    let body: ApiResponse = response.json().map_err(|e| e.to_string())?;
-    if body.code != 200 {
-        return Err(format!("JX3API 返回错误: code={}", body.code));
+/// 同步区服数据：联网获取失败或未开启联网同步时写入兜底数据。
+///
+/// 过程中通过 `jx3-sync-progress` 通知进度，无论成功、失败或取消都会发送 `jx3-sync-finished`；
+/// 取消只在写入数据库之前生效，已开始的写入会在事务内完成。
+pub(crate) async fn run_sync(
+    app: &AppHandle,
+    db: &Database,
+    state: &Jx3SyncState,
+    force_fallback: bool,
+) -> AppResult<SyncSummary> {
+    let guard = state.begin()?;
+    let result = sync_with_guard(app, db, &guard, force_fallback).await;
+    let summary = match &result {
+        Ok(summary) => summary.clone(),
+        Err(err) => SyncSummary::new(SyncResultKind::Failed, 0, Some(err.to_string())),
+    };
+    emit(app, SYNC_FINISHED_EVENT, summary);
+    result
+}
+
+async fn sync_with_guard(
+    app: &AppHandle,
+    db: &Database,
+    guard: &SyncGuard<'_>,
+    force_fallback: bool,
+) -> AppResult<SyncSummary> {
+    let use_network = !force_fallback && sqlite::is_network_sync_enabled(db)?;
+
+    let mut error = None;
+    if use_network {
+        emit(
+            app,
+            SYNC_PROGRESS_EVENT,
+            SyncProgress {
+                stage: SyncStage::Fetching,
+                total: 0,
+            },
+        );
+        match cancellable(fetch_servers(db), &guard.cancel).await {
+            Some(Ok(outcome)) => {
+                let total = match &outcome {
+                    FetchOutcome::Fetched { servers, .. } => servers.len(),
+                    FetchOutcome::NotModified => 0,
+                };
+                emit(
+                    app,
+                    SYNC_PROGRESS_EVENT,
+                    SyncProgress {
+                        stage: SyncStage::Saving,
+                        total,
+                    },
+                );
+                return Ok(match save_fetched(app, db, outcome)? {
+                    SyncOutcome::NotModified => {
+                        SyncSummary::new(SyncResultKind::NotModified, 0, None)
+                    }
+                    SyncOutcome::Updated(count) => {
+                        SyncSummary::new(SyncResultKind::Updated, count, None)
+                    }
+                });
+            }
+            Some(Err(err)) => {
+                println!("区服数据同步失败，使用兜底数据: {err}");
+                error = Some(err.to_string());
+            }
+            None => return Ok(SyncSummary::new(SyncResultKind::Cancelled, 0, None)),
+        }
     }
 
-    parse_server_list(&body.data).ok_or_else(|| "无法解析区服数据".to_string())
+    let servers = jx3_fallback::servers();
+    emit(
+        app,
+        SYNC_PROGRESS_EVENT,
+        SyncProgress {
+            stage: SyncStage::Saving,
+            total: servers.len(),
+        },
+    );
+    let changed = sqlite::save_jx3_servers(db, &servers, false, None)?;
+    emit_changed(app, changed, false);
+
+    Ok(SyncSummary::new(
+        SyncResultKind::FallbackUsed,
+        servers.len(),
+        error,
+    ))
+}
+
+/// 第 `failures` 次连续失败后的等待时间：按刷新间隔指数递增，最长不超过 `MAX_BACKOFF`。
+fn next_delay(interval: Duration, failures: u32) -> Duration {
+    if failures == 0 {
+        return interval;
+    }
+    interval
+        .saturating_mul(1 << failures.min(MAX_BACKOFF_SHIFT))
+        .min(MAX_BACKOFF.max(interval))
+}
+
+/// 读取后台刷新间隔；未开启联网同步或间隔为 0 时返回 `None`。
+fn refresh_interval(db: &Database) -> AppResult<Option<Duration>> {
+    if !sqlite::is_network_sync_enabled(db)? {
+        return Ok(None);
+    }
+    let secs = sqlite::get_jx3_sync_interval(db)?;
+    Ok((secs > 0).then(|| Duration::from_secs(secs)))
+}
+
+/// 后台刷新一次：只写入联网获取的数据，失败时不回退到兜底数据，以免覆盖较新的状态。
+async fn refresh_once(app: &AppHandle, db: &Database, state: &Jx3SyncState) -> AppResult<()> {
+    let guard = state.begin()?;
+    let outcome = match cancellable(fetch_servers(db), &guard.cancel).await {
+        Some(result) => result?,
+        None => return Ok(()),
+    };
+    save_fetched(app, db, outcome)?;
+    Ok(())
+}
+
+/// 启动后台刷新任务：开启联网同步时按设置的间隔刷新区服状态，获取失败后指数退避。
+pub(crate) fn spawn_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let db = app.state::<Database>();
+        let state = app.state::<Jx3SyncState>();
+        let mut failures = 0;
+
+        loop {
+            let interval = match refresh_interval(&db) {
+                Ok(interval) => interval,
+                Err(e) => {
+                    println!("load jx3 sync interval error: {:?}", e);
+                    None
+                }
+            };
+
+            let Some(interval) = interval else {
+                failures = 0;
+                state.settings_changed.notified().await;
+                continue;
+            };
+
+            let delay = next_delay(interval, failures);
+            if cancellable(sleep(delay), &state.settings_changed)
+                .await
+                .is_none()
+            {
+                // 设置已变化，按新设置重新计时
+                continue;
+            }
+
+            match refresh_once(&app, &db, &state).await {
+                Ok(()) => failures = 0,
+                // 手动同步进行中，本轮跳过
+                Err(AppError::Conflict(_)) => {}
+                Err(e) => {
+                    failures += 1;
+                    println!("后台刷新区服数据失败（第 {failures} 次）: {e}");
+                }
+            }
+        }
+    });
 }
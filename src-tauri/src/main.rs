@@ -1,24 +1,74 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod sqlite;
+mod account_csv;
+mod backup;
+mod clipboard;
+mod db;
+mod error;
+mod jx3_fallback;
+mod jx3_provider;
+mod jx3_reset;
 mod jx3_sync;
+mod password_import;
+mod snapshot;
+mod sqlite;
+mod storage;
+mod vault;
 
 mod entity {
     pub mod account;
     pub mod account_category;
+    pub mod backup;
     pub mod category;
+    pub mod csv_import;
     pub mod jx3_server;
+    pub mod jx3_server_merge;
+    pub mod ledger;
+    pub mod password_import;
+    pub mod raid;
     pub mod role;
+    pub mod role_task;
+    pub mod snapshot;
+    pub mod vault_document;
 }
 
-use crate::entity::account::Account;
+use crate::db::Database;
+use crate::entity::account::{Account, AccountField};
+use crate::entity::backup::{BackupSummary, RestoreMode};
 use crate::entity::category::Category;
-use crate::entity::jx3_server::Jx3Server;
+use crate::entity::csv_import::{CsvImportOptions, CsvImportReport, CsvPreview};
+use crate::entity::jx3_server::{
+    Jx3Server, Jx3ServerMaintenance, Jx3ServerStatusRecord, Jx3ServerUptime,
+};
+use crate::entity::jx3_server_merge::{Jx3ServerMerge, MergeReport};
+use crate::entity::ledger::{
+    AccountLedgerBalance, LedgerEntry, LedgerQuery, LedgerRecord, LedgerTransfer,
+};
+use crate::entity::password_import::PasswordImportOptions;
+use crate::entity::raid::{RaidInstance, RaidLockout, RaidLockoutDetail};
+use crate::entity::role::{Role, RoleQuery, RoleSearchResult};
+use crate::entity::role_task::{OutstandingTask, RoleTask, RoleTaskProgress, TaskPeriod};
+use crate::entity::snapshot::{SnapshotInfo, SnapshotReason};
+use crate::entity::vault_document::{VaultDocument, VaultTransferSummary};
+use crate::error::{AppError, AppResult};
+use crate::jx3_fallback::FallbackInfo;
+use crate::jx3_provider::{FetchOutcome, ProviderConfig};
+use crate::jx3_reset::ResetSchedule;
+use crate::jx3_sync::{Jx3SyncState, SyncSummary};
+use crate::storage::DatabasePathSource;
 use serde::{Deserialize, Serialize};
-use tauri::{LogicalSize, Manager, Size};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, LogicalSize, Manager, Size, State};
 // use tauri::{CustomMenuItem, Menu, MenuItem, Submenu};
 
+const VAULT_LOCKED_EVENT: &str = "vault-locked";
+const VAULT_IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// 每日快照的检查间隔。
+const DAILY_SNAPSHOT_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
 #[derive(Debug, Serialize, Deserialize)]
 struct WindowSize {
     width: u32,
@@ -27,151 +77,313 @@ struct WindowSize {
 
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 #[tauri::command]
-fn query_all_accounts() -> Vec<Account> {
-    let result = sqlite::query_all_accounts();
-    match result {
-        Ok(accounts) => accounts,
-        Err(e) => {
-            println!("{}", format!("query_all_accounts error: {:?}", e));
-            vec![]
-        }
-    }
+fn query_all_accounts(db: State<Database>) -> AppResult<Vec<Account>> {
+    sqlite::query_all_accounts(&db)
 }
 
 #[tauri::command]
-fn query_accounts_by_value(account: Account, with_liked: bool, category_id: i32) -> Vec<Account> {
-    let result = sqlite::query_accounts_by_value(&account, with_liked, category_id);
-    match result {
-        Ok(accounts) => accounts,
-        Err(e) => {
-            println!("{}", format!("query_accounts_by_value error: {:?}", e));
-            vec![]
-        }
-    }
+fn query_accounts_by_value(
+    db: State<Database>,
+    account: Account,
+    with_liked: bool,
+    category_id: i32,
+    role_server: Option<String>,
+    role_zone: Option<String>,
+) -> AppResult<Vec<Account>> {
+    sqlite::query_accounts_by_value(
+        &db,
+        &account,
+        with_liked,
+        category_id,
+        role_server.as_deref(),
+        role_zone.as_deref(),
+    )
 }
 
 #[tauri::command]
-fn insert_account(account: Account) -> bool {
-    if let Err(e) = sqlite::insert_account(&account) {
-        println!("{}", format!("insert_account error: {:?}", e));
-        return false;
-    }
+fn insert_account(db: State<Database>, account: Account) -> AppResult<()> {
+    sqlite::insert_account(&db, &account)
+}
 
-    true
+#[tauri::command]
+fn update_account(db: State<Database>, account: Account) -> AppResult<()> {
+    sqlite::update_account(&db, &account)
 }
 
 #[tauri::command]
-fn update_account(account: Account) -> bool {
-    if let Err(e) = sqlite::update_account(&account) {
-        println!("{}", format!("update_account error: {:?}", e));
-        return false;
-    }
+fn update_like(db: State<Database>, id: i32, liked: bool) -> AppResult<()> {
+    sqlite::like_account(&db, id, liked)
+}
 
-    true
+#[tauri::command]
+fn delete_account(db: State<Database>, id: i32) -> AppResult<()> {
+    sqlite::delete_by_id(&db, id)
 }
 
 #[tauri::command]
-fn update_like(id: i32, liked: bool) -> bool {
-    if let Err(e) = sqlite::like_account(id, liked) {
-        println!("like_account error: {:?}", e);
-        return false;
-    }
+fn query_account_roles(db: State<Database>, account_id: i32) -> AppResult<Vec<Role>> {
+    sqlite::query_account_roles(&db, account_id)
+}
 
-    true
+#[tauri::command]
+fn search_roles(db: State<Database>, query: RoleQuery) -> AppResult<Vec<RoleSearchResult>> {
+    sqlite::search_roles(&db, &query)
 }
 
 #[tauri::command]
-fn delete_account(id: i32) -> bool {
-    if let Err(e) = sqlite::delete_by_id(id) {
-        println!("{}", format!("delete error: {:?}", e));
-        return false;
-    }
+fn save_role(db: State<Database>, role: Role) -> AppResult<Role> {
+    sqlite::save_role(&db, &role)
+}
 
-    true
+#[tauri::command]
+fn delete_role(db: State<Database>, id: i32) -> AppResult<()> {
+    sqlite::delete_role(&db, id)
 }
 
 #[tauri::command]
-fn create_category(category: Category) -> bool {
-    if let Err(e) = sqlite::create_category(&category) {
-        println!("{}", format!("insert_category error: {:?}", e));
-        return false;
-    }
+fn get_task_reset_schedule() -> ResetSchedule {
+    sqlite::get_task_reset_schedule()
+}
 
-    true
+#[tauri::command]
+fn query_role_tasks(db: State<Database>) -> AppResult<Vec<RoleTask>> {
+    sqlite::query_role_tasks(&db)
 }
 
 #[tauri::command]
-fn query_all_category() -> Vec<Category> {
-    let result = sqlite::query_all_categories();
-    match result {
-        Ok(categories) => categories,
-        Err(e) => {
-            println!("{}", format!("query_all_category error: {:?}", e));
-            vec![]
-        }
-    }
+fn save_role_task(db: State<Database>, task: RoleTask) -> AppResult<RoleTask> {
+    sqlite::save_role_task(&db, &task)
 }
 
 #[tauri::command]
-fn update_category(category: Category) -> bool {
-    if let Err(e) = sqlite::update_category(&category) {
-        println!("{}", format!("update_category error: {:?}", e));
-        return false;
-    }
+fn delete_role_task(db: State<Database>, id: i32) -> AppResult<()> {
+    sqlite::delete_role_task(&db, id)
+}
 
-    true
+#[tauri::command]
+fn set_role_task_completed(
+    db: State<Database>,
+    role_id: i32,
+    task_id: i32,
+    completed: bool,
+) -> AppResult<Option<i64>> {
+    sqlite::set_role_task_completed(&db, role_id, task_id, completed)
 }
 
 #[tauri::command]
-fn delete_category_by_id(id: i32) -> bool {
-    if let Err(e) = sqlite::delete_category_by_id(id) {
-        println!("{}", format!("delete_category_by_id error: {:?}", e));
-        return false;
-    }
+fn query_role_task_progress(db: State<Database>, role_id: i32) -> AppResult<Vec<RoleTaskProgress>> {
+    sqlite::query_role_task_progress(&db, role_id)
+}
 
-    true
+#[tauri::command]
+fn query_outstanding_tasks(
+    db: State<Database>,
+    period: Option<TaskPeriod>,
+) -> AppResult<Vec<OutstandingTask>> {
+    sqlite::query_outstanding_tasks(&db, period)
 }
 
 #[tauri::command]
-fn reorder_accounts(ids: Vec<i32>) -> bool {
-    if let Err(e) = sqlite::reorder_accounts(&ids) {
-        println!("{}", format!("reorder_accounts error: {:?}", e));
-        return false;
-    }
+fn query_raid_instances(db: State<Database>) -> AppResult<Vec<RaidInstance>> {
+    sqlite::query_raid_instances(&db)
+}
 
-    true
+#[tauri::command]
+fn save_raid_instance(db: State<Database>, raid: RaidInstance) -> AppResult<RaidInstance> {
+    sqlite::save_raid_instance(&db, &raid)
 }
 
 #[tauri::command]
-fn reorder_categories(ids: Vec<i32>) -> bool {
-    if let Err(e) = sqlite::reorder_categories(&ids) {
-        println!("{}", format!("reorder_categories error: {:?}", e));
-        return false;
-    }
+fn delete_raid_instance(db: State<Database>, id: i32) -> AppResult<()> {
+    sqlite::delete_raid_instance(&db, id)
+}
+
+#[tauri::command]
+fn record_raid_lockout(
+    db: State<Database>,
+    role_id: i32,
+    raid_id: i32,
+    locked_at: Option<i64>,
+) -> AppResult<RaidLockout> {
+    sqlite::record_raid_lockout(&db, role_id, raid_id, locked_at)
+}
 
-    true
+#[tauri::command]
+fn clear_raid_lockout(db: State<Database>, role_id: i32, raid_id: i32) -> AppResult<()> {
+    sqlite::clear_raid_lockout(&db, role_id, raid_id)
 }
 
 #[tauri::command]
-fn get_saved_window_size() -> Option<WindowSize> {
-    match sqlite::get_window_size() {
-        Ok(Some((width, height))) => Some(WindowSize { width, height }),
-        Ok(None) => None,
-        Err(e) => {
-            println!("get_saved_window_size error: {:?}", e);
-            None
-        }
-    }
+fn query_role_lockouts(db: State<Database>, role_id: i32) -> AppResult<Vec<RaidLockout>> {
+    sqlite::query_role_lockouts(&db, role_id)
+}
+
+#[tauri::command]
+fn query_raid_lockouts(
+    db: State<Database>,
+    raid_id: Option<i32>,
+) -> AppResult<Vec<RaidLockoutDetail>> {
+    sqlite::query_raid_lockouts(&db, raid_id)
+}
+
+#[tauri::command]
+fn query_raid_available_roles(
+    db: State<Database>,
+    raid_id: i32,
+) -> AppResult<Vec<RoleSearchResult>> {
+    sqlite::query_raid_available_roles(&db, raid_id)
+}
+
+#[tauri::command]
+fn query_ledger(db: State<Database>, query: LedgerQuery) -> AppResult<Vec<LedgerRecord>> {
+    sqlite::query_ledger(&db, &query)
+}
+
+#[tauri::command]
+fn save_ledger_entry(db: State<Database>, entry: LedgerEntry) -> AppResult<i32> {
+    sqlite::save_ledger_entry(&db, &entry)
+}
+
+#[tauri::command]
+fn delete_ledger_entry(db: State<Database>, id: i32) -> AppResult<()> {
+    sqlite::delete_ledger_entry(&db, id)
+}
+
+#[tauri::command]
+fn record_ledger_transfer(db: State<Database>, transfer: LedgerTransfer) -> AppResult<()> {
+    sqlite::record_ledger_transfer(&db, &transfer)
 }
 
 #[tauri::command]
-fn save_window_size(width: u32, height: u32) -> bool {
-    if let Err(e) = sqlite::save_window_size(width, height) {
-        println!("save_window_size error: {:?}", e);
-        return false;
+fn query_ledger_balances(
+    db: State<Database>,
+    account_id: Option<i32>,
+) -> AppResult<Vec<AccountLedgerBalance>> {
+    sqlite::query_ledger_balances(&db, account_id)
+}
+
+/// 把整个账号库导出为 JSON 文件，用户名与密码为明文。
+#[tauri::command]
+fn export_vault(db: State<Database>, path: String) -> AppResult<VaultTransferSummary> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(AppError::Validation("请选择导出文件路径".to_string()));
     }
+    let document = sqlite::export_vault(&db)?;
+    fs::write(path, serde_json::to_string_pretty(&document)?)?;
+    Ok(document.summary())
+}
+
+/// 从 `export_vault` 生成的 JSON 文件导入，合并进当前账号库。
+#[tauri::command]
+fn import_vault(
+    db: State<Database>,
+    sync: State<Jx3SyncState>,
+    path: String,
+) -> AppResult<VaultTransferSummary> {
+    let document = VaultDocument::parse(&fs::read_to_string(path.trim())?)?;
+    let summary = sqlite::import_vault(&db, &document)?;
+    // 导入的设置可能改变了联网同步开关或刷新间隔
+    sync.reschedule();
+    Ok(summary)
+}
+
+/// 从 KeePass XML、Bitwarden JSON 或浏览器密码 CSV 导入账号，返回逐条处理结果。
+#[tauri::command]
+fn import_password_manager(
+    db: State<Database>,
+    options: PasswordImportOptions,
+) -> AppResult<CsvImportReport> {
+    password_import::import_passwords(&db, &options)
+}
+
+/// 用单独的备份口令把账号库与设置加密备份到一个文件。
+#[tauri::command]
+fn create_backup(
+    db: State<Database>,
+    path: String,
+    passphrase: String,
+) -> AppResult<BackupSummary> {
+    backup::create_backup(&db, &path, &passphrase)
+}
 
-    true
+/// 校验备份文件的口令与完整性，返回备份时间、应用版本与数据量。
+#[tauri::command]
+fn inspect_backup(path: String, passphrase: String) -> AppResult<BackupSummary> {
+    backup::inspect_backup(&path, &passphrase)
+}
+
+/// 校验后恢复备份：`replace` 清空当前账号库后恢复，`merge` 合并进当前账号库。
+#[tauri::command]
+fn restore_backup(
+    db: State<Database>,
+    sync: State<Jx3SyncState>,
+    path: String,
+    passphrase: String,
+    mode: RestoreMode,
+) -> AppResult<VaultTransferSummary> {
+    let summary = backup::restore_backup(&db, &path, &passphrase, mode)?;
+    sync.reschedule();
+    Ok(summary)
+}
+
+/// 按“导出设置”中的字段把所有账号导出为 CSV，返回导出的账号数。
+#[tauri::command]
+fn export_accounts_csv(db: State<Database>, path: String) -> AppResult<usize> {
+    account_csv::export_accounts(&db, &path)
+}
+
+#[tauri::command]
+fn preview_accounts_csv(path: String) -> AppResult<CsvPreview> {
+    account_csv::preview(path.trim())
+}
+
+#[tauri::command]
+fn import_accounts_csv(
+    db: State<Database>,
+    options: CsvImportOptions,
+) -> AppResult<CsvImportReport> {
+    account_csv::import_accounts(&db, &options)
+}
+
+#[tauri::command]
+fn create_category(db: State<Database>, category: Category) -> AppResult<()> {
+    sqlite::create_category(&db, &category)
+}
+
+#[tauri::command]
+fn query_all_category(db: State<Database>) -> AppResult<Vec<Category>> {
+    sqlite::query_all_categories(&db)
+}
+
+#[tauri::command]
+fn update_category(db: State<Database>, category: Category) -> AppResult<()> {
+    sqlite::update_category(&db, &category)
+}
+
+#[tauri::command]
+fn delete_category_by_id(db: State<Database>, id: i32) -> AppResult<()> {
+    sqlite::delete_category_by_id(&db, id)
+}
+
+#[tauri::command]
+fn reorder_accounts(db: State<Database>, ids: Vec<i32>) -> AppResult<()> {
+    sqlite::reorder_accounts(&db, &ids)
+}
+
+#[tauri::command]
+fn reorder_categories(db: State<Database>, ids: Vec<i32>) -> AppResult<()> {
+    sqlite::reorder_categories(&db, &ids)
+}
+
+#[tauri::command]
+fn get_saved_window_size(db: State<Database>) -> AppResult<Option<WindowSize>> {
+    Ok(sqlite::get_window_size(&db)?.map(|(width, height)| WindowSize { width, height }))
+}
+
+#[tauri::command]
+fn save_window_size(db: State<Database>, width: u32, height: u32) -> AppResult<()> {
+    sqlite::save_window_size(&db, width, height)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -181,41 +393,26 @@ struct DisplaySettings {
 }
 
 #[tauri::command]
-fn get_display_settings() -> DisplaySettings {
-    match sqlite::get_display_settings() {
-        Ok((hide_username, hide_password)) => DisplaySettings {
-            hide_username,
-            hide_password,
-        },
-        Err(e) => {
-            println!("get_display_settings error: {:?}", e);
-            DisplaySettings {
-                hide_username: false,
-                hide_password: true,
-            }
-        }
-    }
+fn get_display_settings(db: State<Database>) -> AppResult<DisplaySettings> {
+    let (hide_username, hide_password) = sqlite::get_display_settings(&db)?;
+    Ok(DisplaySettings {
+        hide_username,
+        hide_password,
+    })
 }
 
 #[tauri::command]
-fn save_display_settings(hide_username: bool, hide_password: bool) -> bool {
-    if let Err(e) = sqlite::save_display_settings(hide_username, hide_password) {
-        println!("save_display_settings error: {:?}", e);
-        return false;
-    }
-
-    true
+fn save_display_settings(
+    db: State<Database>,
+    hide_username: bool,
+    hide_password: bool,
+) -> AppResult<()> {
+    sqlite::save_display_settings(&db, hide_username, hide_password)
 }
 
 #[tauri::command]
-fn get_app_version() -> String {
-    match sqlite::get_app_version() {
-        Ok(version) => version,
-        Err(e) => {
-            println!("get_app_version error: {:?}", e);
-            "0.0.0".to_string()
-        }
-    }
+fn get_app_version(db: State<Database>) -> AppResult<String> {
+    sqlite::get_app_version(&db)
 }
 
 #[tauri::command]
@@ -224,25 +421,55 @@ fn get_current_app_version() -> String {
 }
 
 #[tauri::command]
-fn query_all_jx3_servers() -> Vec<Jx3Server> {
-    match sqlite::query_all_jx3_servers() {
-        Ok(servers) => servers,
-        Err(e) => {
-            println!("query_all_jx3_servers error: {:?}", e);
-            vec![]
-        }
-    }
+fn query_all_jx3_servers(db: State<Database>) -> AppResult<Vec<Jx3Server>> {
+    sqlite::query_all_jx3_servers(&db)
 }
 
 #[tauri::command]
-fn sync_jx3_servers(force_fallback: bool) -> bool {
-    match sqlite::sync_jx3_servers(force_fallback) {
-        Ok(_) => true,
-        Err(e) => {
-            println!("sync_jx3_servers error: {:?}", e);
-            false
-        }
-    }
+fn set_jx3_server_watched(db: State<Database>, server: String, watched: bool) -> AppResult<()> {
+    sqlite::set_jx3_server_watched(&db, &server, watched)
+}
+
+#[tauri::command]
+fn query_jx3_server_timeline(
+    db: State<Database>,
+    server: String,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> AppResult<Vec<Jx3ServerStatusRecord>> {
+    sqlite::query_jx3_server_timeline(&db, &server, from, to)
+}
+
+#[tauri::command]
+fn get_jx3_server_uptime(
+    db: State<Database>,
+    server: String,
+    window_secs: u64,
+) -> AppResult<Jx3ServerUptime> {
+    sqlite::get_jx3_server_uptime(&db, &server, window_secs)
+}
+
+#[tauri::command]
+fn get_jx3_server_last_maintenance(
+    db: State<Database>,
+    server: String,
+) -> AppResult<Jx3ServerMaintenance> {
+    sqlite::get_jx3_server_last_maintenance(&db, &server)
+}
+
+#[tauri::command]
+async fn sync_jx3_servers(
+    app: AppHandle,
+    db: State<'_, Database>,
+    sync: State<'_, Jx3SyncState>,
+    force_fallback: bool,
+) -> AppResult<SyncSummary> {
+    jx3_sync::run_sync(&app, &db, &sync, force_fallback).await
+}
+
+#[tauri::command]
+fn cancel_jx3_sync(sync: State<Jx3SyncState>) -> bool {
+    sync.cancel()
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -253,77 +480,411 @@ struct NetworkSyncSettings {
 }
 
 #[tauri::command]
-fn get_network_sync_settings() -> NetworkSyncSettings {
-    match sqlite::get_network_sync_settings() {
-        Ok(settings) => NetworkSyncSettings {
-            enabled: settings.enabled,
-            prompted: settings.prompted,
-            last_sync: settings.last_sync,
-        },
-        Err(e) => {
-            println!("get_network_sync_settings error: {:?}", e);
-            NetworkSyncSettings {
-                enabled: false,
-                prompted: false,
-                last_sync: None,
-            }
-        }
+fn get_network_sync_settings(db: State<Database>) -> AppResult<NetworkSyncSettings> {
+    let settings = sqlite::get_network_sync_settings(&db)?;
+    Ok(NetworkSyncSettings {
+        enabled: settings.enabled,
+        prompted: settings.prompted,
+        last_sync: settings.last_sync,
+    })
+}
+
+#[tauri::command]
+fn save_network_sync_settings(
+    db: State<Database>,
+    sync: State<Jx3SyncState>,
+    enabled: bool,
+    prompted: bool,
+) -> AppResult<()> {
+    sqlite::save_network_sync_settings(&db, enabled, prompted)?;
+    sync.reschedule();
+    Ok(())
+}
+
+#[tauri::command]
+fn query_jx3_server_merges(db: State<Database>) -> AppResult<Vec<Jx3ServerMerge>> {
+    sqlite::query_jx3_server_merges(&db)
+}
+
+#[tauri::command]
+fn save_jx3_server_merge(db: State<Database>, merge: Jx3ServerMerge) -> AppResult<()> {
+    sqlite::save_jx3_server_merge(&db, &merge)
+}
+
+#[tauri::command]
+fn delete_jx3_server_merge(db: State<Database>, old_server: String) -> AppResult<()> {
+    sqlite::delete_jx3_server_merge(&db, &old_server)
+}
+
+/// 按已生效的合服记录迁移角色所在区服；`dry_run` 为 true 时只返回将受影响的账号。
+#[tauri::command]
+fn apply_jx3_server_merges(db: State<Database>, dry_run: bool) -> AppResult<MergeReport> {
+    sqlite::apply_jx3_server_merges(&db, dry_run)
+}
+
+#[tauri::command]
+fn get_jx3_fallback_info() -> FallbackInfo {
+    jx3_fallback::info()
+}
+
+#[tauri::command]
+fn get_jx3_provider_config(db: State<Database>) -> AppResult<ProviderConfig> {
+    sqlite::get_jx3_provider_config(&db)
+}
+
+#[tauri::command]
+fn save_jx3_provider_config(db: State<Database>, config: ProviderConfig) -> AppResult<()> {
+    sqlite::save_jx3_provider_config(&db, &config)
+}
+
+/// 用给定的数据来源设置试取一次区服数据，返回解析出的区服数量，不写入数据库。
+#[tauri::command]
+async fn test_jx3_provider(config: ProviderConfig) -> AppResult<usize> {
+    config.validate()?;
+    // 不带缓存校验信息，服务器不会返回 304
+    match config.build().fetch(None).await? {
+        FetchOutcome::Fetched { servers, .. } => Ok(servers.len()),
+        FetchOutcome::NotModified => Ok(0),
     }
 }
 
 #[tauri::command]
-fn save_network_sync_settings(enabled: bool, prompted: bool) -> bool {
-    match sqlite::save_network_sync_settings(enabled, prompted) {
-        Ok(_) => true,
-        Err(e) => {
-            println!("save_network_sync_settings error: {:?}", e);
-            false
-        }
+fn get_jx3_maintenance_status(db: State<Database>) -> AppResult<String> {
+    sqlite::get_jx3_maintenance_status(&db)
+}
+
+#[tauri::command]
+fn save_jx3_maintenance_status(db: State<Database>, status: String) -> AppResult<()> {
+    sqlite::save_jx3_maintenance_status(&db, &status)
+}
+
+#[tauri::command]
+fn get_jx3_sync_interval(db: State<Database>) -> AppResult<u64> {
+    sqlite::get_jx3_sync_interval(&db)
+}
+
+#[tauri::command]
+fn save_jx3_sync_interval(
+    db: State<Database>,
+    sync: State<Jx3SyncState>,
+    secs: u64,
+) -> AppResult<()> {
+    sqlite::save_jx3_sync_interval(&db, secs)?;
+    sync.reschedule();
+    Ok(())
+}
+
+#[tauri::command]
+fn get_export_fields(db: State<Database>) -> AppResult<Vec<String>> {
+    sqlite::get_export_fields(&db)
+}
+
+#[tauri::command]
+fn save_export_fields(db: State<Database>, fields: Vec<String>) -> AppResult<()> {
+    sqlite::save_export_fields(&db, &fields)
+}
+
+#[tauri::command]
+fn get_favorite_filter(db: State<Database>) -> AppResult<i32> {
+    sqlite::get_favorite_filter(&db)
+}
+
+#[tauri::command]
+fn save_favorite_filter(db: State<Database>, value: i32) -> AppResult<()> {
+    sqlite::save_favorite_filter(&db, value)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VaultStatus {
+    initialized: bool,
+    unlocked: bool,
+    pending_encryption: bool,
+}
+
+#[tauri::command]
+fn get_vault_status(db: State<Database>) -> AppResult<VaultStatus> {
+    let status = sqlite::get_vault_status(&db)?;
+    Ok(VaultStatus {
+        initialized: status.initialized,
+        unlocked: status.unlocked,
+        pending_encryption: status.pending_encryption,
+    })
+}
+
+#[tauri::command]
+fn setup_vault(db: State<Database>, password: String) -> AppResult<()> {
+    sqlite::setup_vault(&db, &password)
+}
+
+#[tauri::command]
+fn unlock_vault(db: State<Database>, password: String) -> AppResult<()> {
+    sqlite::unlock_vault(&db, &password)
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct VaultLockedPayload {
+    reason: &'static str,
+}
+
+fn emit_vault_locked(app: &AppHandle, reason: &'static str) {
+    if let Err(e) = app.emit_all(VAULT_LOCKED_EVENT, VaultLockedPayload { reason }) {
+        println!("emit {VAULT_LOCKED_EVENT} error: {:?}", e);
     }
 }
 
 #[tauri::command]
-fn get_export_fields() -> Vec<String> {
-    match sqlite::get_export_fields() {
-        Ok(fields) => fields,
-        Err(e) => {
-            println!("get_export_fields error: {:?}", e);
-            vec![]
-        }
+fn lock_vault(app: AppHandle) {
+    if vault::lock() {
+        emit_vault_locked(&app, "manual");
     }
 }
 
 #[tauri::command]
-fn save_export_fields(fields: Vec<String>) -> bool {
-    match sqlite::save_export_fields(&fields) {
-        Ok(_) => true,
-        Err(e) => {
-            println!("save_export_fields error: {:?}", e);
-            false
+fn report_vault_activity() {
+    vault::touch();
+}
+
+#[tauri::command]
+fn get_vault_idle_timeout(db: State<Database>) -> AppResult<u64> {
+    sqlite::get_vault_idle_timeout(&db)
+}
+
+#[tauri::command]
+fn save_vault_idle_timeout(db: State<Database>, secs: u64) -> AppResult<()> {
+    sqlite::save_vault_idle_timeout(&db, secs)
+}
+
+/// 后台轮询闲置时间，超时后锁定账号库并通知前端清空列表。
+fn spawn_vault_idle_watcher(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(VAULT_IDLE_CHECK_INTERVAL);
+        if vault::lock_if_idle() {
+            emit_vault_locked(&app, "idle");
+        }
+    });
+}
+
+/// 后台定时检查，距上次每日快照超过一天时为当前账号库创建新快照。
+fn spawn_daily_snapshots(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let db = app.state::<Database>();
+        if let Err(e) = snapshot::take_daily_snapshot_if_due(&db) {
+            println!("创建每日快照失败: {:?}", e);
         }
+        std::thread::sleep(DAILY_SNAPSHOT_CHECK_INTERVAL);
+    });
+}
+
+#[tauri::command]
+fn copy_account_field(
+    db: State<Database>,
+    app: AppHandle,
+    id: i32,
+    field: AccountField,
+) -> AppResult<()> {
+    let value = sqlite::get_account_field(&db, id, field)?;
+    let clear_after = sqlite::get_clipboard_clear_seconds(&db)?;
+
+    clipboard::copy_with_auto_clear(&app, value, clear_after)
+        .map_err(|e| AppError::Storage(format!("写入剪贴板失败: {e}")))
+}
+
+/// 复制前端生成的文本；`sensitive` 为 `true`（如分享内容含密码）时按设置自动清除。
+#[tauri::command]
+fn copy_text(db: State<Database>, app: AppHandle, text: String, sensitive: bool) -> AppResult<()> {
+    let clear_after = if sensitive {
+        sqlite::get_clipboard_clear_seconds(&db)?
+    } else {
+        0
+    };
+
+    clipboard::copy_with_auto_clear(&app, text, clear_after)
+        .map_err(|e| AppError::Storage(format!("写入剪贴板失败: {e}")))
+}
+
+#[tauri::command]
+fn get_clipboard_clear_seconds(db: State<Database>) -> AppResult<u64> {
+    sqlite::get_clipboard_clear_seconds(&db)
+}
+
+#[tauri::command]
+fn save_clipboard_clear_seconds(db: State<Database>, secs: u64) -> AppResult<()> {
+    sqlite::save_clipboard_clear_seconds(&db, secs)
+}
+
+#[derive(Debug, Serialize)]
+struct DatabaseLocation {
+    path: String,
+    source: DatabasePathSource,
+    default_path: String,
+}
+
+fn app_data_dir(app: &AppHandle) -> PathBuf {
+    app.path_resolver()
+        .app_data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+#[tauri::command]
+fn get_database_location(db: State<Database>, app: AppHandle) -> DatabaseLocation {
+    let data_dir = app_data_dir(&app);
+    let source = storage::resolve_database_location(&data_dir).source;
+
+    DatabaseLocation {
+        path: db.path().to_string_lossy().into_owned(),
+        source,
+        default_path: data_dir
+            .join(storage::DB_FILE_NAME)
+            .to_string_lossy()
+            .into_owned(),
     }
 }
 
+/// 修改数据库位置并立即切换；`path` 为空时恢复默认位置。
 #[tauri::command]
-fn get_favorite_filter() -> i32 {
-    match sqlite::get_favorite_filter() {
-        Ok(value) => value,
-        Err(e) => {
-            println!("get_favorite_filter error: {:?}", e);
-            0
-        }
+fn set_database_path(db: State<Database>, app: AppHandle, path: Option<String>) -> AppResult<()> {
+    let data_dir = app_data_dir(&app);
+    if storage::resolve_database_location(&data_dir).source == DatabasePathSource::Env {
+        return Err(AppError::Validation(format!(
+            "已通过环境变量 {} 指定数据库位置",
+            storage::DB_PATH_ENV
+        )));
+    }
+
+    let path = path
+        .filter(|value| !value.trim().is_empty())
+        .map(PathBuf::from);
+
+    // 自定义位置只作用于默认账号库：默认账号库使用中时迁移并切换，否则只复制其文件；
+    // 先迁移再保存设置，迁移失败时设置保持不变，下次启动仍打开原来的数据库
+    let target = path
+        .clone()
+        .unwrap_or_else(|| data_dir.join(storage::DB_FILE_NAME));
+    let (active, _) = storage::list_profiles(&data_dir);
+    if active == storage::DEFAULT_PROFILE_ID {
+        sqlite::relocate_database(&db, &target)?;
+        emit_vault_locked(&app, "relocate");
+    } else {
+        let current = storage::resolve_database_location(&data_dir).path;
+        sqlite::copy_database_file(&current, &target)?;
     }
+    storage::save_database_path_override(&data_dir, path.as_deref())?;
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct VaultProfileInfo {
+    id: String,
+    name: String,
+    path: String,
+}
+
+#[derive(Debug, Serialize)]
+struct VaultProfileList {
+    active: String,
+    profiles: Vec<VaultProfileInfo>,
 }
 
 #[tauri::command]
-fn save_favorite_filter(value: i32) -> bool {
-    match sqlite::save_favorite_filter(value) {
-        Ok(_) => true,
-        Err(e) => {
-            println!("save_favorite_filter error: {:?}", e);
-            false
-        }
+fn list_vault_profiles(app: AppHandle) -> VaultProfileList {
+    let data_dir = app_data_dir(&app);
+    let (active, profiles) = storage::list_profiles(&data_dir);
+
+    VaultProfileList {
+        active,
+        profiles: profiles
+            .iter()
+            .map(|profile| VaultProfileInfo {
+                id: profile.id.clone(),
+                name: profile.name.clone(),
+                path: storage::profile_path(&data_dir, profile)
+                    .to_string_lossy()
+                    .into_owned(),
+            })
+            .collect(),
+    }
+}
+
+#[tauri::command]
+fn create_vault_profile(app: AppHandle, name: String) -> AppResult<()> {
+    let data_dir = app_data_dir(&app);
+    let profile = storage::create_profile(&data_dir, &name)?;
+
+    if let Err(e) = sqlite::create_database_file(&storage::profile_path(&data_dir, &profile)) {
+        let _ = storage::delete_profile(&data_dir, &profile.id);
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+fn rename_vault_profile(app: AppHandle, id: String, name: String) -> AppResult<()> {
+    Ok(storage::rename_profile(&app_data_dir(&app), &id, &name)?)
+}
+
+#[tauri::command]
+fn delete_vault_profile(app: AppHandle, id: String) -> AppResult<()> {
+    let data_dir = app_data_dir(&app);
+    let profile = storage::find_deletable_profile(&data_dir, &id)?;
+    // 档案文件会被永久删除，先保存一份快照；快照失败时不删除
+    snapshot::take_file_snapshot(
+        &storage::profile_path(&data_dir, &profile),
+        SnapshotReason::Delete,
+    )?;
+    Ok(storage::delete_profile(&data_dir, &id)?)
+}
+
+#[tauri::command]
+fn switch_vault_profile(
+    db: State<Database>,
+    sync: State<Jx3SyncState>,
+    app: AppHandle,
+    id: String,
+) -> AppResult<()> {
+    let data_dir = app_data_dir(&app);
+    let profile = storage::find_profile(&data_dir, &id)?;
+
+    sqlite::switch_database(&db, &storage::profile_path(&data_dir, &profile))?;
+    // 各账号库的联网同步设置相互独立
+    sync.reschedule();
+    if let Err(e) = storage::set_active_profile(&data_dir, &id) {
+        println!("switch_vault_profile error: {:?}", e);
     }
+    emit_vault_locked(&app, "switch");
+
+    Ok(())
+}
+
+/// 列出当前账号库的自动快照，新快照在前。
+#[tauri::command]
+fn list_snapshots(db: State<Database>) -> AppResult<Vec<SnapshotInfo>> {
+    snapshot::list_snapshots(&db)
+}
+
+/// 用快照覆盖当前账号库，恢复前会为当前数据再创建一份快照；恢复后账号库被锁定。
+#[tauri::command]
+fn restore_snapshot(
+    db: State<Database>,
+    sync: State<Jx3SyncState>,
+    app: AppHandle,
+    name: String,
+) -> AppResult<()> {
+    snapshot::restore_snapshot(&db, &name)?;
+    sync.reschedule();
+    emit_vault_locked(&app, "restore");
+    Ok(())
+}
+
+#[tauri::command]
+fn get_snapshot_retention(db: State<Database>) -> AppResult<u32> {
+    sqlite::get_snapshot_retention(&db)
+}
+
+#[tauri::command]
+fn save_snapshot_retention(db: State<Database>, count: u32) -> AppResult<()> {
+    sqlite::save_snapshot_retention(&db, count)
 }
 
 #[tauri::command]
@@ -333,10 +894,7 @@ fn get_default_window_size() -> WindowSize {
     #[cfg(not(target_os = "macos"))]
     let height = 732u32;
 
-    WindowSize {
-        width: 460,
-        height,
-    }
+    WindowSize { width: 460, height }
 }
 
 // fn main() {
@@ -356,7 +914,24 @@ fn get_default_window_size() -> WindowSize {
 // }
 
 fn main() {
-    sqlite::initialize_database().expect("数据库初始化失败");
+    let context = tauri::generate_context!();
+    let data_dir =
+        tauri::api::path::app_data_dir(context.config()).unwrap_or_else(|| PathBuf::from("."));
+    let resource_path =
+        tauri::api::path::resource_dir(context.package_info(), &tauri::Env::default())
+            .map(|dir| dir.join(jx3_fallback::FALLBACK_RESOURCE));
+    jx3_fallback::load(
+        resource_path.as_deref(),
+        &data_dir.join(jx3_fallback::FALLBACK_OVERRIDE_FILE_NAME),
+    );
+    let default_location = storage::resolve_database_location(&data_dir);
+    if default_location.source == DatabasePathSource::Default {
+        if let Err(e) = storage::migrate_legacy_database(&default_location.path) {
+            println!("迁移旧数据库失败: {:?}", e);
+        }
+    }
+    let database = sqlite::initialize_database(&storage::resolve_active_database(&data_dir))
+        .expect("数据库初始化失败");
     // 这里 `"quit".to_string()` 定义菜单项 ID，第二个参数是菜单项标签。
     // let quit = CustomMenuItem::new("quit".to_string(), "Quit");
     // let close = CustomMenuItem::new("close".to_string(), "Close");
@@ -366,7 +941,6 @@ fn main() {
     //     .add_item(CustomMenuItem::new("hide", "Hide"))
     //     .add_submenu(submenu);
 
-
     tauri::Builder::default()
         // .menu(menu)
         // .on_menu_event(|event| {
@@ -380,17 +954,24 @@ fn main() {
         //         _ => {}
         //     }
         // })
+        .manage(database)
+        .manage(Jx3SyncState::default())
         .setup(|app| {
-            let window = app
-                .get_window("main")
-                .expect("main window not found");
-
-            if let Ok(Some((width, height))) = sqlite::get_window_size() {
-                let _ = window.set_size(Size::Logical(LogicalSize::new(
-                    width as f64,
-                    height as f64,
-                )));
+            let window = app.get_window("main").expect("main window not found");
+            let db = app.state::<Database>();
+
+            if let Ok(Some((width, height))) = sqlite::get_window_size(&db) {
+                let _ =
+                    window.set_size(Size::Logical(LogicalSize::new(width as f64, height as f64)));
+            }
+
+            match sqlite::get_vault_idle_timeout(&db) {
+                Ok(secs) => vault::set_idle_timeout(secs),
+                Err(e) => println!("load vault idle timeout error: {:?}", e),
             }
+            spawn_vault_idle_watcher(app.handle());
+            jx3_sync::spawn_scheduler(app.handle());
+            spawn_daily_snapshots(app.handle());
 
             let _ = window.show();
             Ok(())
@@ -402,6 +983,43 @@ fn main() {
             update_like,
             insert_account,
             update_account,
+            query_account_roles,
+            search_roles,
+            save_role,
+            delete_role,
+            get_task_reset_schedule,
+            query_role_tasks,
+            save_role_task,
+            delete_role_task,
+            set_role_task_completed,
+            query_role_task_progress,
+            query_outstanding_tasks,
+            query_raid_instances,
+            save_raid_instance,
+            delete_raid_instance,
+            record_raid_lockout,
+            clear_raid_lockout,
+            query_role_lockouts,
+            query_raid_lockouts,
+            query_raid_available_roles,
+            query_ledger,
+            save_ledger_entry,
+            delete_ledger_entry,
+            record_ledger_transfer,
+            query_ledger_balances,
+            export_vault,
+            import_vault,
+            create_backup,
+            inspect_backup,
+            restore_backup,
+            list_snapshots,
+            restore_snapshot,
+            get_snapshot_retention,
+            save_snapshot_retention,
+            export_accounts_csv,
+            preview_accounts_csv,
+            import_accounts_csv,
+            import_password_manager,
             create_category,
             query_all_category,
             update_category,
@@ -416,14 +1034,49 @@ fn main() {
             get_app_version,
             get_current_app_version,
             query_all_jx3_servers,
+            set_jx3_server_watched,
+            query_jx3_server_timeline,
+            get_jx3_server_uptime,
+            get_jx3_server_last_maintenance,
             sync_jx3_servers,
+            cancel_jx3_sync,
             get_network_sync_settings,
             save_network_sync_settings,
+            query_jx3_server_merges,
+            save_jx3_server_merge,
+            delete_jx3_server_merge,
+            apply_jx3_server_merges,
+            get_jx3_fallback_info,
+            get_jx3_provider_config,
+            save_jx3_provider_config,
+            get_jx3_maintenance_status,
+            save_jx3_maintenance_status,
+            test_jx3_provider,
+            get_jx3_sync_interval,
+            save_jx3_sync_interval,
             get_export_fields,
             save_export_fields,
             get_favorite_filter,
-            save_favorite_filter
+            save_favorite_filter,
+            get_vault_status,
+            setup_vault,
+            unlock_vault,
+            lock_vault,
+            report_vault_activity,
+            get_vault_idle_timeout,
+            save_vault_idle_timeout,
+            copy_account_field,
+            copy_text,
+            get_clipboard_clear_seconds,
+            save_clipboard_clear_seconds,
+            get_database_location,
+            set_database_path,
+            list_vault_profiles,
+            create_vault_profile,
+            rename_vault_profile,
+            delete_vault_profile,
+            switch_vault_profile
         ])
-        .run(tauri::generate_context!())
+        .run(context)
         .expect("error while running tauri application");
 }
@@ -0,0 +1,443 @@
+use crate::account_csv::{self, CsvRowError};
+use crate::db::Database;
+use crate::entity::csv_import::{CsvAccountRow, CsvDuplicateKey, CsvImportReport, CsvRowAction};
+use crate::entity::password_import::{PasswordImportFormat, PasswordImportOptions};
+use crate::error::{AppError, AppResult};
+use crate::sqlite;
+use roxmltree::Node;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+
+/// 分组路径的分隔符，KeePass 的多级分组合并为一个分类名称。
+const GROUP_PATH_SEPARATOR: &str = " / ";
+
+/// 解析结果：可导入的条目与跳过的条目（序号、名称、原因）。
+type ParsedEntries = (Vec<CsvAccountRow>, Vec<CsvRowError>);
+
+/// 去除首尾空白后的非空值，用于标题、网址、备注与文件夹名称。
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// 原样保留的非空值，用于用户名与密码：首尾空白可能是凭据的一部分。
+fn non_empty_raw(value: Option<&str>) -> Option<String> {
+    value.filter(|value| !value.is_empty()).map(str::to_string)
+}
+
+/// 取网址中的主机名，用作没有标题的条目名称。
+fn url_host(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = rest.split(['/', '?', '#']).next()?;
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    let host = host.split(':').next()?.trim();
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+/// 把一条登录信息转换为导入行；用户名与密码都为空时返回跳过原因。
+fn build_row(
+    line: usize,
+    title: Option<String>,
+    url: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    notes: Option<String>,
+    categories: Vec<String>,
+) -> Result<CsvAccountRow, CsvRowError> {
+    let name = title
+        .or_else(|| url.as_deref().and_then(url_host))
+        .or_else(|| username.clone())
+        .unwrap_or_default();
+    if username.is_none() && password.is_none() {
+        return Err((line, name, "没有用户名和密码".to_string()));
+    }
+    if name.is_empty() {
+        return Err((line, name, "没有标题".to_string()));
+    }
+    Ok(CsvAccountRow {
+        line,
+        name,
+        username,
+        password,
+        description: notes,
+        categories,
+        roles: Vec::new(),
+    })
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn child_text(node: Node, name: &str) -> Option<String> {
+    non_empty(child(node, name).and_then(|child| child.text()))
+}
+
+/// 原样读取 KeePass 条目的 `String` 字段，另外返回加密保存（`Protected="True"`）而无法读取的字段名。
+fn keepass_fields(entry: Node) -> (HashMap<String, String>, Option<String>) {
+    let mut fields = HashMap::new();
+    let mut protected = None;
+    for field in entry
+        .children()
+        .filter(|child| child.has_tag_name("String"))
+    {
+        let Some(key) = child_text(field, "Key") else {
+            continue;
+        };
+        let Some(value) = child(field, "Value") else {
+            continue;
+        };
+        if value.attribute("Protected") == Some("True") {
+            protected.get_or_insert(key);
+        } else if let Some(text) = non_empty_raw(value.text()) {
+            fields.insert(key, text);
+        }
+    }
+    (fields, protected)
+}
+
+/// `in_recycle_bin` 为上级分组是否位于回收站，回收站的下级分组同样跳过。
+fn walk_keepass_group(
+    group: Node,
+    path: &[String],
+    recycle_bin: Option<&str>,
+    in_recycle_bin: bool,
+    entries: &mut ParsedEntries,
+) {
+    let in_recycle_bin = in_recycle_bin
+        || (recycle_bin.is_some() && child_text(group, "UUID").as_deref() == recycle_bin);
+    for node in group.children() {
+        if node.has_tag_name("Entry") {
+            let line = entries.0.len() + entries.1.len() + 1;
+            let (fields, protected) = keepass_fields(node);
+            let get = |key: &str| non_empty(fields.get(key).map(String::as_str));
+            let get_raw = |key: &str| fields.get(key).cloned();
+            if let Some(key) = protected {
+                entries.1.push((
+                    line,
+                    get("Title").unwrap_or_default(),
+                    format!("字段“{key}”已加密，请在 KeePass 中使用「导出为 KeePass XML (2.x)」"),
+                ));
+                continue;
+            }
+            let row = build_row(
+                line,
+                get("Title"),
+                get("URL"),
+                get_raw("UserName"),
+                get_raw("Password"),
+                get("Notes"),
+                (!path.is_empty())
+                    .then(|| path.join(GROUP_PATH_SEPARATOR))
+                    .into_iter()
+                    .collect(),
+            );
+            match row {
+                Ok(row) if in_recycle_bin => {
+                    entries.1.push((line, row.name, "位于回收站".to_string()))
+                }
+                Ok(row) => entries.0.push(row),
+                Err(skipped) => entries.1.push(skipped),
+            }
+        } else if node.has_tag_name("Group") {
+            let mut child_path = path.to_vec();
+            child_path.extend(child_text(node, "Name"));
+            walk_keepass_group(node, &child_path, recycle_bin, in_recycle_bin, entries);
+        }
+    }
+}
+
+/// 解析 KeePass 2.x XML 导出。根分组不作为分类，下级分组按路径合并为分类名称，历史版本不导入。
+fn read_keepass_xml(text: &str) -> AppResult<ParsedEntries> {
+    let document = roxmltree::Document::parse(text)
+        .map_err(|e| AppError::Validation(format!("XML 格式错误: {e}")))?;
+    let file = document.root_element();
+    if !file.has_tag_name("KeePassFile") {
+        return Err(AppError::Validation(
+            "不是 KeePass XML 导出文件".to_string(),
+        ));
+    }
+    let recycle_bin = child(file, "Meta").and_then(|meta| child_text(meta, "RecycleBinUUID"));
+    let root_group = child(file, "Root")
+        .and_then(|root| child(root, "Group"))
+        .ok_or_else(|| AppError::Validation("KeePass XML 中没有分组".to_string()))?;
+
+    let mut entries = (Vec::new(), Vec::new());
+    walk_keepass_group(root_group, &[], recycle_bin.as_deref(), false, &mut entries);
+    Ok(entries)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitwardenExport {
+    #[serde(default)]
+    encrypted: bool,
+    #[serde(default)]
+    folders: Vec<BitwardenFolder>,
+    items: Option<Vec<BitwardenItem>>,
+}
+
+#[derive(Deserialize)]
+struct BitwardenFolder {
+    id: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BitwardenItem {
+    #[serde(rename = "type")]
+    kind: u8,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    folder_id: Option<String>,
+    #[serde(default)]
+    login: Option<BitwardenLogin>,
+}
+
+#[derive(Deserialize)]
+struct BitwardenLogin {
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+    #[serde(default)]
+    uris: Option<Vec<BitwardenUri>>,
+}
+
+#[derive(Deserialize)]
+struct BitwardenUri {
+    #[serde(default)]
+    uri: Option<String>,
+}
+
+/// Bitwarden 条目类型：1 登录、2 安全笔记、3 支付卡、4 身份、5 SSH 密钥。
+fn bitwarden_kind_name(kind: u8) -> &'static str {
+    match kind {
+        2 => "安全笔记",
+        3 => "支付卡",
+        4 => "身份",
+        5 => "SSH 密钥",
+        _ => "未知类型",
+    }
+}
+
+/// 解析 Bitwarden 未加密 JSON 导出，只导入登录条目，文件夹作为分类。
+fn read_bitwarden_json(text: &str) -> AppResult<ParsedEntries> {
+    let export: BitwardenExport = serde_json::from_str(text)
+        .map_err(|e| AppError::Validation(format!("不是有效的 Bitwarden JSON 导出: {e}")))?;
+    if export.encrypted {
+        return Err(AppError::Validation(
+            "加密的 Bitwarden 导出无法导入，请选择「.json」格式重新导出".to_string(),
+        ));
+    }
+    let Some(items) = export.items else {
+        return Err(AppError::Validation("Bitwarden 导出中没有条目".to_string()));
+    };
+    let folders: HashMap<String, String> = export
+        .folders
+        .into_iter()
+        .map(|folder| (folder.id, folder.name))
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut skipped = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        let line = index + 1;
+        let title = non_empty(item.name.as_deref());
+        let login = match item.login {
+            Some(login) if item.kind == 1 => login,
+            _ => {
+                skipped.push((
+                    line,
+                    title.unwrap_or_default(),
+                    format!("{}条目不是账号密码", bitwarden_kind_name(item.kind)),
+                ));
+                continue;
+            }
+        };
+        let url = login
+            .uris
+            .unwrap_or_default()
+            .into_iter()
+            .find_map(|uri| non_empty(uri.uri.as_deref()));
+        let categories = item
+            .folder_id
+            .and_then(|id| folders.get(&id))
+            .and_then(|name| non_empty(Some(name)))
+            .into_iter()
+            .collect();
+        match build_row(
+            line,
+            title,
+            url,
+            non_empty_raw(login.username.as_deref()),
+            non_empty_raw(login.password.as_deref()),
+            non_empty(item.notes.as_deref()),
+            categories,
+        ) {
+            Ok(row) => rows.push(row),
+            Err(entry) => skipped.push(entry),
+        }
+    }
+    Ok((rows, skipped))
+}
+
+/// 解析浏览器导出的密码 CSV，按表头识别列（兼容 Chrome / Edge、Firefox、Safari），序号为 CSV 行号。
+fn read_browser_csv(path: &str) -> AppResult<ParsedEntries> {
+    let mut records = account_csv::read_records(path)?.into_iter();
+    let headers: Vec<String> = records
+        .next()
+        .ok_or_else(|| AppError::Validation("CSV 文件为空".to_string()))?
+        .cells
+        .iter()
+        .map(|header| header.to_lowercase())
+        .collect();
+    let find = |names: &[&str]| {
+        headers
+            .iter()
+            .position(|header| names.contains(&header.as_str()))
+    };
+    let name_column = find(&["name", "title"]);
+    let url_column = find(&["url", "origin_url"]);
+    let username_column = find(&["username", "username_value", "login"]);
+    let password_column = find(&["password", "password_value"]);
+    let notes_column = find(&["note", "notes"]);
+    if username_column.is_none() || password_column.is_none() {
+        return Err(AppError::Validation(
+            "未识别的浏览器密码 CSV：缺少 username 或 password 列".to_string(),
+        ));
+    }
+
+    let mut rows = Vec::new();
+    let mut skipped = Vec::new();
+    for record in records {
+        if record.is_blank() {
+            continue;
+        }
+        let raw = |column: Option<usize>| {
+            column
+                .and_then(|column| record.cells.get(column))
+                .map(String::as_str)
+        };
+        let cell = |column: Option<usize>| non_empty(raw(column));
+        match build_row(
+            record.line,
+            cell(name_column),
+            cell(url_column),
+            non_empty_raw(raw(username_column)),
+            non_empty_raw(raw(password_column)),
+            cell(notes_column),
+            Vec::new(),
+        ) {
+            Ok(row) => rows.push(row),
+            Err(entry) => skipped.push(entry),
+        }
+    }
+    Ok((rows, skipped))
+}
+
+/// 从其他密码管理器的导出文件导入账号，预演与 CSV 导入一致；名称与用户名都相同才视为重复，
+/// 无法导入的条目以“跳过”计入报告并附上原因。
+pub(crate) fn import_passwords(
+    db: &Database,
+    options: &PasswordImportOptions,
+) -> AppResult<CsvImportReport> {
+    let path = options.path.trim();
+    if path.is_empty() {
+        return Err(AppError::Validation("请选择要导入的文件".to_string()));
+    }
+    let read_text = || -> AppResult<String> {
+        Ok(fs::read_to_string(path)?
+            .trim_start_matches('\u{feff}')
+            .to_string())
+    };
+    let (rows, skipped) = match options.format {
+        PasswordImportFormat::KeepassXml => read_keepass_xml(&read_text()?)?,
+        PasswordImportFormat::BitwardenJson => read_bitwarden_json(&read_text()?)?,
+        PasswordImportFormat::BrowserCsv => read_browser_csv(path)?,
+    };
+
+    let mut report = CsvImportReport {
+        dry_run: options.dry_run,
+        ..CsvImportReport::default()
+    };
+    for (line, name, reason) in skipped {
+        report.push(line, &name, CsvRowAction::Skipped, Some(reason));
+    }
+    sqlite::import_csv_accounts(
+        db,
+        &rows,
+        CsvDuplicateKey::NameAndUsername,
+        options.duplicate,
+        &mut report,
+    )?;
+    report.rows.sort_by_key(|row| row.line);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keepass_skips_recycle_bin_subgroups_and_keeps_secrets_untrimmed() {
+        let xml = r#"<KeePassFile>
+            <Meta><RecycleBinUUID>BIN</RecycleBinUUID></Meta>
+            <Root><Group>
+                <UUID>ROOT</UUID><Name>数据库</Name>
+                <Group>
+                    <UUID>WORK</UUID><Name>工作</Name>
+                    <Entry>
+                        <String><Key>Title</Key><Value> 邮箱 </Value></String>
+                        <String><Key>UserName</Key><Value>alice</Value></String>
+                        <String><Key>Password</Key><Value> secret </Value></String>
+                    </Entry>
+                </Group>
+                <Group>
+                    <UUID>BIN</UUID><Name>回收站</Name>
+                    <Group>
+                        <UUID>OLD</UUID><Name>旧分组</Name>
+                        <Entry>
+                            <String><Key>Title</Key><Value>已删除</Value></String>
+                            <String><Key>Password</Key><Value>deleted</Value></String>
+                        </Entry>
+                    </Group>
+                </Group>
+            </Group></Root>
+        </KeePassFile>"#;
+
+        let (rows, skipped) = read_keepass_xml(xml).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].name, "邮箱");
+        assert_eq!(rows[0].password.as_deref(), Some(" secret "));
+        assert_eq!(rows[0].categories, ["工作"]);
+        assert_eq!(
+            skipped,
+            [(2, "已删除".to_string(), "位于回收站".to_string())]
+        );
+    }
+
+    #[test]
+    fn bitwarden_keeps_secrets_untrimmed() {
+        let json = r#"{
+            "encrypted": false,
+            "items": [{
+                "type": 1,
+                "name": " 论坛 ",
+                "login": { "username": " bob", "password": "pass ", "uris": [{ "uri": "https://bbs.example.com/" }] }
+            }]
+        }"#;
+
+        let (rows, _) = read_bitwarden_json(json).unwrap();
+        assert_eq!(rows[0].name, "论坛");
+        assert_eq!(rows[0].username.as_deref(), Some(" bob"));
+        assert_eq!(rows[0].password.as_deref(), Some("pass "));
+    }
+}
@@ -0,0 +1,240 @@
+use crate::db::Database;
+use crate::entity::snapshot::{SnapshotInfo, SnapshotReason};
+use crate::error::{AppError, AppResult};
+use crate::sqlite;
+use crate::vault;
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 快照目录名，位于数据库文件所在目录下。
+const SNAPSHOT_DIR: &str = "snapshots";
+const SNAPSHOT_EXTENSION: &str = "db";
+/// 两次每日快照的最小间隔。
+const DAILY_INTERVAL_SECS: i64 = 24 * 60 * 60;
+/// 在线备份每步复制的页数与步间暂停，避免长时间占用数据库。
+const BACKUP_PAGES_PER_STEP: std::os::raw::c_int = 256;
+const BACKUP_STEP_PAUSE: Duration = Duration::from_millis(10);
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// 数据库对应的快照目录：`snapshots/<数据库文件名>/`，各账号库档案的快照互不混杂。
+fn snapshot_dir(db_path: &Path) -> PathBuf {
+    let file_name = db_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    db_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(SNAPSHOT_DIR)
+        .join(file_name)
+}
+
+/// 从快照文件名 `<Unix 时间戳>-<原因>[-序号].db` 解析出 `(创建时间, 原因, 序号)`，没有序号时为 1。
+fn parse_file_name(name: &str) -> Option<(i64, SnapshotReason, u32)> {
+    let stem = name.strip_suffix(&format!(".{SNAPSHOT_EXTENSION}"))?;
+    let mut parts = stem.splitn(3, '-');
+    let created_at = parts.next()?.parse().ok()?;
+    let reason = SnapshotReason::parse(parts.next()?)?;
+    let sequence = match parts.next() {
+        Some(sequence) => sequence.parse().ok()?,
+        None => 1,
+    };
+    Some((created_at, reason, sequence))
+}
+
+fn read_snapshots(dir: &Path) -> AppResult<Vec<SnapshotInfo>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut snapshots = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some((created_at, reason, sequence)) = parse_file_name(&name) else {
+            continue;
+        };
+        let info = SnapshotInfo {
+            size: entry.metadata()?.len(),
+            name,
+            reason,
+            created_at,
+        };
+        snapshots.push((info, sequence));
+    }
+    // 新快照在前
+    snapshots.sort_by(|(a, a_seq), (b, b_seq)| (b.created_at, b_seq).cmp(&(a.created_at, a_seq)));
+    Ok(snapshots.into_iter().map(|(info, _)| info).collect())
+}
+
+/// `reasons` 中的每种原因只保留最新的 `retention` 份快照。
+fn prune(dir: &Path, retention: u32, reasons: &[SnapshotReason]) -> AppResult<()> {
+    let snapshots = read_snapshots(dir)?;
+    for &reason in reasons {
+        for snapshot in snapshots
+            .iter()
+            .filter(|snapshot| snapshot.reason == reason)
+            .skip(retention as usize)
+        {
+            fs::remove_file(dir.join(&snapshot.name))?;
+        }
+    }
+    Ok(())
+}
+
+/// 用 SQLite 在线备份接口把 `conn` 的主库复制为快照，并清理超出保留数量的旧快照。
+///
+/// 保留数量设置为 0 或数据库不在磁盘上时不创建快照，返回 `None`。
+pub(crate) fn take_snapshot_with_conn(
+    conn: &Connection,
+    reason: SnapshotReason,
+) -> AppResult<Option<SnapshotInfo>> {
+    write_snapshot(conn, reason, false)
+}
+
+/// `force` 为真时忽略保留数量为 0 的设置，仍创建快照，并只保留该原因最新的一份。
+fn write_snapshot(
+    conn: &Connection,
+    reason: SnapshotReason,
+    force: bool,
+) -> AppResult<Option<SnapshotInfo>> {
+    let Some(db_path) = conn.path().filter(|path| !path.is_empty()) else {
+        return Ok(None);
+    };
+    let retention = sqlite::get_snapshot_retention_with_conn(conn)?;
+    if retention == 0 && !force {
+        return Ok(None);
+    }
+
+    let dir = snapshot_dir(Path::new(db_path));
+    fs::create_dir_all(&dir)?;
+    let created_at = unix_now();
+    let base = format!("{created_at}-{}", reason.as_str());
+    let mut name = format!("{base}.{SNAPSHOT_EXTENSION}");
+    let mut sequence = 1;
+    while dir.join(&name).exists() {
+        sequence += 1;
+        name = format!("{base}-{sequence}.{SNAPSHOT_EXTENSION}");
+    }
+
+    let path = dir.join(&name);
+    let mut target = Connection::open(&path)?;
+    let copied = Backup::new(conn, &mut target).and_then(|backup| {
+        backup.run_to_completion(BACKUP_PAGES_PER_STEP, BACKUP_STEP_PAUSE, None)
+    });
+    drop(target);
+    if let Err(e) = copied {
+        let _ = fs::remove_file(&path);
+        return Err(e.into());
+    }
+
+    if retention == 0 {
+        prune(&dir, 1, &[reason])?;
+    } else {
+        prune(&dir, retention, &SnapshotReason::ALL)?;
+    }
+    Ok(Some(SnapshotInfo {
+        size: fs::metadata(&path)?.len(),
+        name,
+        reason,
+        created_at,
+    }))
+}
+
+/// 为未打开的数据库文件创建快照，不受保留数量设置影响；用于删除账号库档案前保留其数据。
+///
+/// 快照位于该文件所在目录的 `snapshots/<文件名>/` 下，文件不存在时返回 `None`。
+pub(crate) fn take_file_snapshot(
+    path: &Path,
+    reason: SnapshotReason,
+) -> AppResult<Option<SnapshotInfo>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    write_snapshot(&conn, reason, true)
+}
+
+/// 在有风险的操作前创建快照；失败只记录日志，不阻止操作本身。
+pub(crate) fn snapshot_before_with_conn(conn: &Connection, reason: SnapshotReason) {
+    if let Err(e) = take_snapshot_with_conn(conn, reason) {
+        println!("创建{}快照失败: {:?}", reason.as_str(), e);
+    }
+}
+
+pub(crate) fn snapshot_before(db: &Database, reason: SnapshotReason) {
+    match db.get() {
+        Ok(conn) => snapshot_before_with_conn(&conn, reason),
+        Err(e) => println!("创建{}快照失败: {:?}", reason.as_str(), e),
+    }
+}
+
+/// 距上次每日快照已超过一天时创建一份，返回新建的快照。
+pub(crate) fn take_daily_snapshot_if_due(db: &Database) -> AppResult<Option<SnapshotInfo>> {
+    let last_daily = list_snapshots(db)?
+        .into_iter()
+        .find(|snapshot| snapshot.reason == SnapshotReason::Daily);
+    if last_daily.is_some_and(|snapshot| unix_now() - snapshot.created_at < DAILY_INTERVAL_SECS) {
+        return Ok(None);
+    }
+    take_snapshot_with_conn(&*db.get()?, SnapshotReason::Daily)
+}
+
+/// 列出当前账号库的快照，新快照在前。
+pub(crate) fn list_snapshots(db: &Database) -> AppResult<Vec<SnapshotInfo>> {
+    read_snapshots(&snapshot_dir(&db.path()))
+}
+
+/// 用快照覆盖当前数据库。
+///
+/// 快照先完整读入内存并校验，再为当前数据创建一份 `restore` 快照以便撤销，最后通过在线备份接口写回。
+/// 撤销快照不受保留数量设置影响，始终创建。
+/// 恢复后按需执行迁移，并锁定账号库：快照中的主密码可能与当前不同。
+pub(crate) fn restore_snapshot(db: &Database, name: &str) -> AppResult<()> {
+    if Path::new(name).file_name().and_then(|n| n.to_str()) != Some(name)
+        || parse_file_name(name).is_none()
+    {
+        return Err(AppError::Validation(format!("无效的快照名称: {name}")));
+    }
+    let path = snapshot_dir(&db.path()).join(name);
+    if !path.exists() {
+        return Err(AppError::NotFound(format!("快照不存在: {name}")));
+    }
+
+    let source = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut staged = Connection::open_in_memory()?;
+    Backup::new(&source, &mut staged)?.run_to_completion(
+        BACKUP_PAGES_PER_STEP,
+        BACKUP_STEP_PAUSE,
+        None,
+    )?;
+    drop(source);
+    let check: String = staged.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+    if check != "ok" {
+        return Err(AppError::Validation(format!("快照文件已损坏: {check}")));
+    }
+
+    write_snapshot(&*db.get()?, SnapshotReason::Restore, true)?;
+
+    let mut conn = db.get()?;
+    Backup::new(&staged, &mut conn)?.run_to_completion(
+        BACKUP_PAGES_PER_STEP,
+        BACKUP_STEP_PAUSE,
+        None,
+    )?;
+    drop(conn);
+
+    vault::lock();
+    sqlite::reload_database(db)
+}
@@ -1,18 +1,82 @@
-use crate::entity::account::Account;
+use crate::db::Database;
+use crate::entity::account::{Account, AccountField};
+use crate::entity::account_category::AccountCategory;
 use crate::entity::category::Category;
-use crate::entity::jx3_server::Jx3Server;
-use crate::entity::role::Role;
-use crate::jx3_sync::{fallback_servers, fetch_servers_from_api};
-use rusqlite::{params, Connection, Result, ToSql};
+use crate::entity::csv_import::{
+    CsvAccountRow, CsvDuplicateKey, CsvDuplicateStrategy, CsvImportReport, CsvRowAction,
+};
+use crate::entity::jx3_server::{
+    Jx3Server, Jx3ServerChange, Jx3ServerMaintenance, Jx3ServerStatusRecord, Jx3ServerStatusShare,
+    Jx3ServerUptime,
+};
+use crate::entity::jx3_server_merge::{AffectedAccount, Jx3ServerMerge, MergeReport, MigratedRole};
+use crate::entity::ledger::{
+    AccountLedgerBalance, LedgerCounterparty, LedgerEntry, LedgerQuery, LedgerRecord,
+    LedgerTransfer, RoleLedgerBalance,
+};
+use crate::entity::raid::{RaidInstance, RaidLockout, RaidLockoutDetail};
+use crate::entity::role::{Role, RoleQuery, RoleSearchResult};
+use crate::entity::role_task::{OutstandingTask, RoleTask, RoleTaskProgress, TaskPeriod};
+use crate::entity::snapshot::SnapshotReason;
+use crate::entity::vault_document::{
+    RaidLockoutExport, TaskRecordExport, VaultDocument, VaultTransferSummary,
+    VAULT_DOCUMENT_FORMAT, VAULT_DOCUMENT_VERSION,
+};
+use crate::error::{AppError, AppResult};
+use crate::jx3_fallback;
+use crate::jx3_provider::{CacheValidators, ProviderConfig};
+use crate::jx3_reset::{self, ResetSchedule};
+use crate::snapshot;
+use crate::storage;
+use crate::vault::{self, VaultError, VaultKey, VaultMeta};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Result, ToSql};
 use std::cmp::Ordering;
-use std::sync::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
 const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 const APP_VERSION_KEY: &str = "app_version";
-
-lazy_static::lazy_static! {
-    static ref DB_CONNECTION: Mutex<Connection> = Mutex::new(Connection::open("auto_paste.db").unwrap());
-}
+const VAULT_SALT_KEY: &str = "vault_salt";
+const VAULT_KDF_KEY: &str = "vault_kdf";
+const VAULT_CHECK_KEY: &str = "vault_check";
+const VAULT_PENDING_ENCRYPTION_KEY: &str = "vault_pending_encryption";
+const VAULT_IDLE_TIMEOUT_KEY: &str = "vault_idle_timeout";
+const CLIPBOARD_CLEAR_SECONDS_KEY: &str = "clipboard_clear_seconds";
+const DEFAULT_CLIPBOARD_CLEAR_SECONDS: u64 = 30;
+/// 每种快照保留的份数，0 表示不自动创建快照。
+const SNAPSHOT_RETENTION_KEY: &str = "snapshot_retention";
+const DEFAULT_SNAPSHOT_RETENTION: u32 = 7;
+const MAX_SNAPSHOT_RETENTION: u32 = 100;
+const JX3_SYNC_INTERVAL_KEY: &str = "jx3_sync_interval";
+const JX3_PROVIDER_KEY: &str = "jx3_status_provider";
+/// 数据来源中表示维护的状态文字，用于计算最近一次维护时间。
+const JX3_MAINTENANCE_STATUS_KEY: &str = "jx3_maintenance_status";
+const JX3_FALLBACK_VERSION_KEY: &str = "jx3_fallback_version";
+/// 上次联网获取区服数据时的 ETag / Last-Modified，用于条件请求。
+const JX3_HTTP_CACHE_KEY: &str = "jx3_http_cache";
+/// 随账号库导出、导入的设置项；主密码校验信息、数据版本与同步缓存只属于本机数据库，不迁移。
+const TRANSFERABLE_SETTING_KEYS: &[&str] = &[
+    "window_width",
+    "window_height",
+    "hide_username",
+    "hide_password",
+    "export_fields",
+    "favorite_filter",
+    "network_sync_enabled",
+    "network_sync_prompted",
+    VAULT_IDLE_TIMEOUT_KEY,
+    CLIPBOARD_CLEAR_SECONDS_KEY,
+    SNAPSHOT_RETENTION_KEY,
+    JX3_SYNC_INTERVAL_KEY,
+    JX3_PROVIDER_KEY,
+    JX3_MAINTENANCE_STATUS_KEY,
+];
+const DEFAULT_JX3_SYNC_INTERVAL_SECS: u64 = 600;
+/// 后台刷新间隔下限，避免频繁请求 JX3API。
+const MIN_JX3_SYNC_INTERVAL_SECS: u64 = 60;
+/// 默认的维护状态文字；数据来源使用其他文字时可在设置中修改。
+const DEFAULT_JX3_MAINTENANCE_STATUS: &str = "维护";
 
 fn parse_version(version: &str) -> (u32, u32, u32) {
     let mut parts = version.split('.');
@@ -242,9 +306,13 @@ fn migrate_to_1_1_4(conn: &Connection) -> Result<()> {
         [],
     )?;
 
-    upsert_jx3_servers_with_conn(conn, &fallback_servers())?;
+    seed_jx3_servers_with_conn(conn, &jx3_fallback::servers())?;
 
-    ensure_setting_default(conn, "export_fields", r#"["name","username","password","roles","description"]"#)?;
+    ensure_setting_default(
+        conn,
+        "export_fields",
+        r#"["name","username","password","roles","description"]"#,
+    )?;
     ensure_setting_default(conn, "network_sync_enabled", "0")?;
     ensure_setting_default(conn, "network_sync_prompted", "0")?;
     ensure_setting_default(conn, "favorite_filter", "0")?;
@@ -252,6 +320,155 @@ fn migrate_to_1_1_4(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+fn migrate_to_1_2_0(conn: &Connection) -> Result<()> {
+    if !column_exists(conn, "jx3_server", "watched")? {
+        conn.execute(
+            "ALTER TABLE jx3_server ADD COLUMN watched INTEGER NOT NULL DEFAULT 0",
+            [],
+        )?;
+    }
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS jx3_server_status_history (
+            id INTEGER PRIMARY KEY,
+            server TEXT NOT NULL,
+            zone TEXT NOT NULL,
+            status TEXT NOT NULL,
+            changed_at INTEGER NOT NULL
+        );",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_jx3_server_status_history_server
+            ON jx3_server_status_history(server, changed_at)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS jx3_server_merge (
+            id INTEGER PRIMARY KEY,
+            old_server TEXT NOT NULL UNIQUE,
+            new_server TEXT NOT NULL,
+            effective_date TEXT NOT NULL,
+            applied_at INTEGER
+        );",
+        [],
+    )?;
+    for (column, definition) in [
+        ("name", "TEXT"),
+        ("school", "TEXT"),
+        ("body_type", "TEXT"),
+        ("level", "INTEGER"),
+        ("equipment_score", "INTEGER"),
+        ("notes", "TEXT"),
+    ] {
+        if !column_exists(conn, "role", column)? {
+            conn.execute(
+                &format!("ALTER TABLE role ADD COLUMN {column} {definition}"),
+                [],
+            )?;
+        }
+    }
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS role_task (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            period TEXT NOT NULL,
+            description TEXT,
+            sequence INTEGER,
+            enabled INTEGER NOT NULL DEFAULT 1
+        );",
+        [],
+    )?;
+    // role_id 为 role 表主键；period_start 为完成时所在周期的开始时间，周期刷新后记录自然失效
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS role_task_record (
+            id INTEGER PRIMARY KEY,
+            role_id INTEGER NOT NULL,
+            task_id INTEGER NOT NULL,
+            period_start INTEGER NOT NULL,
+            completed_at INTEGER NOT NULL,
+            UNIQUE (role_id, task_id, period_start)
+        );",
+        [],
+    )?;
+
+    // 模式为空时存空字符串，使唯一约束对“不区分模式”的副本同样生效
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS raid_instance (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            mode TEXT NOT NULL DEFAULT '',
+            sequence INTEGER,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            UNIQUE (name, mode)
+        );",
+        [],
+    )?;
+    // role_id 为 role 表主键；expires_at 为获得 CD 后的下一次周常刷新时间，过期即视为 CD 解除
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS raid_lockout (
+            id INTEGER PRIMARY KEY,
+            role_id INTEGER NOT NULL,
+            raid_id INTEGER NOT NULL,
+            locked_at INTEGER NOT NULL,
+            expires_at INTEGER NOT NULL,
+            UNIQUE (role_id, raid_id, expires_at)
+        );",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_raid_lockout_raid ON raid_lockout(raid_id, expires_at)",
+        [],
+    )?;
+    // role_id、counterparty_role_id 为 role 表主键；amount 为正表示收入，为负表示支出
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS role_ledger (
+            id INTEGER PRIMARY KEY,
+            role_id INTEGER NOT NULL,
+            occurred_at INTEGER NOT NULL,
+            currency TEXT NOT NULL,
+            amount INTEGER NOT NULL,
+            counterparty_role_id INTEGER,
+            note TEXT
+        );",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_role_ledger_role ON role_ledger(role_id, currency, occurred_at)",
+        [],
+    )?;
+
+    // 加密需要主密码派生的密钥：迁移时登记存量明文数据，由 setup_vault / unlock_vault 在同一事务内原地加密，
+    // 加密完成前账号数据无法被读取（decrypt_with 拒绝明文）
+    let plaintext_rows: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM account WHERE username NOT LIKE 'enc:v1:%' OR password NOT LIKE 'enc:v1:%'",
+        [],
+        |row| row.get(0),
+    )?;
+    ensure_setting_default(
+        conn,
+        VAULT_PENDING_ENCRYPTION_KEY,
+        if plaintext_rows > 0 { "1" } else { "0" },
+    )?;
+    ensure_setting_default(
+        conn,
+        VAULT_IDLE_TIMEOUT_KEY,
+        &vault::DEFAULT_IDLE_TIMEOUT_SECS.to_string(),
+    )?;
+    ensure_setting_default(
+        conn,
+        CLIPBOARD_CLEAR_SECONDS_KEY,
+        &DEFAULT_CLIPBOARD_CLEAR_SECONDS.to_string(),
+    )?;
+    ensure_setting_default(
+        conn,
+        JX3_SYNC_INTERVAL_KEY,
+        &DEFAULT_JX3_SYNC_INTERVAL_SECS.to_string(),
+    )?;
+
+    Ok(())
+}
+
 fn ensure_setting_default(conn: &Connection, key: &str, default_value: &str) -> Result<()> {
     if get_setting_with_conn(conn, key)?.is_none() {
         set_setting_with_conn(conn, key, default_value)?;
@@ -311,6 +528,7 @@ fn merge_database_schema(conn: &Connection) -> Result<()> {
 
     migrate_to_1_1_0(conn)?;
     migrate_to_1_1_4(conn)?;
+    migrate_to_1_2_0(conn)?;
     ensure_triggers(conn)?;
 
     Ok(())
@@ -340,6 +558,12 @@ const MIGRATIONS: &[Migration] = &[
         description: "添加角色区服表与 JX3 区服字典",
         migrate: migrate_to_1_1_4,
     },
+    Migration {
+        version: "1.2.0",
+        description:
+            "账号库主密码加密，登记待加密的明文账号；补充角色详细信息、日常周常、副本 CD 与角色账本",
+        migrate: migrate_to_1_2_0,
+    },
 ];
 
 fn get_stored_db_version(conn: &Connection) -> Result<String> {
@@ -356,17 +580,20 @@ fn run_migrations(conn: &Connection) -> Result<()> {
         return Ok(());
     }
 
-    println!(
-        "数据库版本({stored_version})与当前版本({current_version})不一致，开始执行合并..."
-    );
+    println!("数据库版本({stored_version})与当前版本({current_version})不一致，开始执行合并...");
 
     if version_lt(current_version, &stored_version) {
-        println!(
-            "警告: 数据库版本({stored_version})高于应用版本({current_version})，跳过结构迁移"
-        );
+        println!("警告: 数据库版本({stored_version})高于应用版本({current_version})，跳过结构迁移");
         return Ok(());
     }
 
+    // 迁移可能重建数据表，已有账号时先留一份快照
+    let account_count: i64 =
+        conn.query_row("SELECT COUNT(*) FROM account", [], |row| row.get(0))?;
+    if account_count > 0 {
+        snapshot::snapshot_before_with_conn(conn, SnapshotReason::Migration);
+    }
+
     for migration in MIGRATIONS {
         if version_lt(&stored_version, migration.version)
             && !version_lt(current_version, migration.version)
@@ -387,8 +614,7 @@ fn run_migrations(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
-pub(crate) fn create_if_not_exists() -> Result<()> {
-    let conn = DB_CONNECTION.lock().unwrap();
+fn create_if_not_exists(conn: &Connection) -> Result<()> {
     // 创建 account 表
     conn.execute(
         "CREATE TABLE IF NOT EXISTS account (
@@ -427,7 +653,7 @@ pub(crate) fn create_if_not_exists() -> Result<()> {
     )?;
 
     // 创建 account 触发器
-    ensure_triggers(&conn)?;
+    ensure_triggers(conn)?;
 
     conn.execute(
         "CREATE TABLE IF NOT EXISTS app_setting (
@@ -437,28 +663,250 @@ pub(crate) fn create_if_not_exists() -> Result<()> {
         [],
     )?;
 
-    run_migrations(&conn)?;
+    run_migrations(conn)?;
+    refresh_fallback_servers(conn)?;
+
+    Ok(())
+}
+
+/// 兜底区服列表版本比数据库记录的新时，补充新增区服、更新所属大区并写入随附的合服记录。
+///
+/// 已有区服的状态保留不变，它们可能来自比兜底数据更新的联网同步；
+/// 合服记录只在版本更新时写入，用户删除或修改过的记录不会在每次同步时被还原。
+fn refresh_fallback_servers(conn: &Connection) -> Result<()> {
+    let version = jx3_fallback::version();
+    let stored: u32 = get_setting_with_conn(conn, JX3_FALLBACK_VERSION_KEY)?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    if stored >= version {
+        return Ok(());
+    }
+
+    let batch = conn.unchecked_transaction()?;
+    let mut stmt = batch.prepare(
+        "INSERT INTO jx3_server (zone, server, status, last_update_time)
+         VALUES (?1, ?2, ?3, datetime('now'))
+         ON CONFLICT(server) DO UPDATE SET zone = excluded.zone",
+    )?;
+    for server in jx3_fallback::servers() {
+        stmt.execute(params![server.zone, server.server, server.status])?;
+    }
+    drop(stmt);
+    upsert_jx3_server_merges_with_conn(&batch, &jx3_fallback::merges())?;
+    set_setting_with_conn(&batch, JX3_FALLBACK_VERSION_KEY, &version.to_string())?;
+    batch.commit()?;
+
+    println!("兜底区服列表已更新至版本 {version}");
+    Ok(())
+}
+
+/// 打开数据库文件，创建表结构并检查/合并数据库版本。
+pub(crate) fn initialize_database(path: &Path) -> AppResult<Database> {
+    storage::ensure_parent_dir(path)?;
+    let db = Database::open(path)?;
+    create_if_not_exists(&*db.get()?)?;
+    Ok(db)
+}
+
+/// 数据库内容被整体替换（如从快照恢复）后调用：补齐表结构、执行迁移并重新应用闲置锁定时间。
+pub(crate) fn reload_database(db: &Database) -> AppResult<()> {
+    create_if_not_exists(&*db.get()?)?;
+    vault::set_idle_timeout(get_vault_idle_timeout(db)?);
+    Ok(())
+}
+
+/// 运行时切换到另一个数据库文件，文件不存在时新建并执行完整的建表与迁移。
+///
+/// 切换后账号库会被锁定，需要使用目标库的主密码重新解锁；闲置锁定时间改用目标库的设置。
+pub(crate) fn switch_database(db: &Database, path: &Path) -> AppResult<()> {
+    if path == db.path() {
+        return Ok(());
+    }
+
+    db.replace(initialize_database(path)?);
+    vault::lock();
+    vault::set_idle_timeout(get_vault_idle_timeout(db)?);
+
+    Ok(())
+}
+
+/// 迁移当前数据库到新位置；目标不存在时先把当前数据库完整复制过去，再切换。
+pub(crate) fn relocate_database(db: &Database, path: &Path) -> AppResult<()> {
+    if !path.exists() {
+        storage::ensure_parent_dir(path)?;
+        db.get()?
+            .execute("VACUUM INTO ?1", params![path.to_string_lossy()])?;
+    }
+
+    switch_database(db, path)
+}
+
+/// 把未打开的数据库文件完整复制到新位置，目标已存在或源文件不存在时不做处理。
+pub(crate) fn copy_database_file(source: &Path, target: &Path) -> AppResult<()> {
+    if target.exists() || !source.is_file() {
+        return Ok(());
+    }
+    storage::ensure_parent_dir(target)?;
+    let conn = Connection::open_with_flags(source, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    conn.execute("VACUUM INTO ?1", params![target.to_string_lossy()])?;
+    Ok(())
+}
+
+/// 新建一个独立的账号库文件并执行全部迁移，不影响当前连接。
+pub(crate) fn create_database_file(path: &Path) -> AppResult<()> {
+    initialize_database(path)?;
+    Ok(())
+}
+
+fn vault_error(err: VaultError) -> rusqlite::Error {
+    rusqlite::Error::ToSqlConversionFailure(Box::new(err))
+}
+
+fn decrypt_column(key: &VaultKey, row: &rusqlite::Row, index: usize, aad: &[u8]) -> Result<String> {
+    let stored: String = row.get(index)?;
+    vault::decrypt_with(key, &stored, aad)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
+fn get_vault_meta_with_conn(conn: &Connection) -> Result<Option<VaultMeta>> {
+    let salt = get_setting_with_conn(conn, VAULT_SALT_KEY)?;
+    let kdf = get_setting_with_conn(conn, VAULT_KDF_KEY)?;
+    let check = get_setting_with_conn(conn, VAULT_CHECK_KEY)?;
+
+    match (salt, kdf, check) {
+        (Some(salt), Some(kdf), Some(check)) => Ok(Some(VaultMeta { salt, kdf, check })),
+        _ => Ok(None),
+    }
+}
+
+/// 将尚未加密的用户名/密码原地加密，已加密的值保持不变。
+fn encrypt_plaintext_accounts(conn: &Connection, key: &VaultKey) -> Result<usize> {
+    let mut stmt = conn.prepare("SELECT id, username, password FROM account")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i32>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+
+    let mut pending = Vec::new();
+    for row in rows {
+        let (id, username, password) = row?;
+        if !vault::is_encrypted(&username) || !vault::is_encrypted(&password) {
+            pending.push((id, username, password));
+        }
+    }
+
+    for (id, username, password) in &pending {
+        let username = if vault::is_encrypted(username) {
+            username.clone()
+        } else {
+            vault::encrypt_with(key, username, &vault::username_aad(*id)).map_err(vault_error)?
+        };
+        let password = if vault::is_encrypted(password) {
+            password.clone()
+        } else {
+            vault::encrypt_with(key, password, &vault::password_aad(*id)).map_err(vault_error)?
+        };
+        conn.execute(
+            "UPDATE account SET username = ?, password = ? WHERE id = ?",
+            params![username, password, id],
+        )?;
+    }
+
+    Ok(pending.len())
+}
+
+/// 加密并写入账号的用户名与密码。附加认证数据绑定账号 id，新账号需先插入取得 id 再写入。
+fn write_credentials_with_conn(
+    conn: &Connection,
+    key: &VaultKey,
+    account_id: i32,
+    username: &str,
+    password: &str,
+) -> Result<()> {
+    let username = vault::encrypt_with(key, username, &vault::username_aad(account_id))
+        .map_err(vault_error)?;
+    let password = vault::encrypt_with(key, password, &vault::password_aad(account_id))
+        .map_err(vault_error)?;
+    conn.execute(
+        "UPDATE account SET username = ?, password = ? WHERE id = ?",
+        params![username, password, account_id],
+    )?;
+    Ok(())
+}
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct VaultStatus {
+    pub(crate) initialized: bool,
+    pub(crate) unlocked: bool,
+    pub(crate) pending_encryption: bool,
+}
+
+pub(crate) fn get_vault_status(db: &Database) -> AppResult<VaultStatus> {
+    let conn = db.get()?;
+    Ok(VaultStatus {
+        initialized: get_vault_meta_with_conn(&conn)?.is_some(),
+        unlocked: vault::is_unlocked(),
+        pending_encryption: get_setting_with_conn(&conn, VAULT_PENDING_ENCRYPTION_KEY)?
+            .map(|v| v == "1")
+            .unwrap_or(false),
+    })
+}
+
+/// 首次设置主密码，并在同一事务内加密已有的明文账号。
+pub(crate) fn setup_vault(db: &Database, password: &str) -> AppResult<()> {
+    if get_vault_meta_with_conn(&*db.get()?)?.is_some() {
+        return Err(VaultError::AlreadyInitialized.into());
+    }
+
+    // 密钥派生耗时较长，放在持锁之外执行
+    let (meta, key) = vault::create(password)?;
+
+    let mut conn = db.get()?;
+    let batch = conn.transaction()?;
+    if get_vault_meta_with_conn(&batch)?.is_some() {
+        return Err(VaultError::AlreadyInitialized.into());
+    }
+    set_setting_with_conn(&batch, VAULT_SALT_KEY, &meta.salt)?;
+    set_setting_with_conn(&batch, VAULT_KDF_KEY, &meta.kdf)?;
+    set_setting_with_conn(&batch, VAULT_CHECK_KEY, &meta.check)?;
+    encrypt_plaintext_accounts(&batch, &key)?;
+    set_setting_with_conn(&batch, VAULT_PENDING_ENCRYPTION_KEY, "0")?;
+    batch.commit()?;
 
+    vault::set_key(key);
     Ok(())
 }
 
-/// 应用启动时调用：创建表结构并检查/合并数据库版本。
-pub(crate) fn initialize_database() -> Result<()> {
-    create_if_not_exists()
+pub(crate) fn unlock_vault(db: &Database, password: &str) -> AppResult<()> {
+    let meta = get_vault_meta_with_conn(&*db.get()?)?.ok_or(VaultError::NotInitialized)?;
+    let key = vault::open(&meta, password)?;
+
+    let mut conn = db.get()?;
+    let batch = conn.transaction()?;
+    // 兜底：外部导入等途径写入的明文数据在解锁时补齐加密
+    encrypt_plaintext_accounts(&batch, &key)?;
+    set_setting_with_conn(&batch, VAULT_PENDING_ENCRYPTION_KEY, "0")?;
+    batch.commit()?;
+
+    vault::set_key(key);
+    Ok(())
 }
 
-pub(crate) fn insert_account(account: &Account) -> Result<()> {
-    let conn = &mut DB_CONNECTION.lock().unwrap();
+pub(crate) fn insert_account(db: &Database, account: &Account) -> AppResult<()> {
+    let key = vault::current_key()?;
+
+    let mut conn = db.get()?;
     let batch = conn.transaction()?;
 
     let default_description = "这个人好懒,没有给他写备注信息呢┓(´∀`)┏".to_string();
     batch.execute(
         "INSERT INTO account (name, username, password, sequence, liked, description, last_update_time)
-        VALUES (?, ?, ?, IFNULL(?, 1), ?, ?, datetime('now'))",
+        VALUES (?, '', '', IFNULL(?, 1), ?, ?, datetime('now'))",
         params![
             account.name,
-            account.username,
-            account.password,
             account.sequence,
             account.liked.unwrap_or(false),
             if account.description.is_none() || account.description.clone().unwrap().is_empty() { default_description } else { account.description.clone().unwrap() },
@@ -466,6 +914,13 @@ pub(crate) fn insert_account(account: &Account) -> Result<()> {
     )?;
 
     let account_id = batch.last_insert_rowid() as i32;
+    write_credentials_with_conn(
+        &batch,
+        &key,
+        account_id,
+        &account.username,
+        &account.password,
+    )?;
 
     if let Some(account_category_ids) = &account.account_category_ids {
         for category_id in account_category_ids {
@@ -484,65 +939,85 @@ pub(crate) fn insert_account(account: &Account) -> Result<()> {
     Ok(())
 }
 
-pub(crate) fn update_account(account: &Account) -> Result<()> {
-    if account.id.is_none() {
-        insert_account(account)?;
-    } else {
-        let conn = &mut DB_CONNECTION.lock().unwrap();
-        let batch = conn.transaction()?;
-
-        let default_description = "这个人好懒,没有给他写备注信息呢┓(´∀`)┏".to_string();
-        batch.execute(
-            "UPDATE account SET name = ?, username = ?, password = ?, sequence = ?, liked = ?, description = ? WHERE id = ?",
-            params![
-                account.name,
-                account.username,
-                account.password,
-                account.sequence,
-                account.liked,
-                if account.description.is_none() || account.description.clone().unwrap().is_empty() { default_description } else { account.description.clone().unwrap() },
-                account.id,
-            ],
-        )?;
+pub(crate) fn update_account(db: &Database, account: &Account) -> AppResult<()> {
+    match account.id {
+        None => insert_account(db, account)?,
+        Some(account_id) => {
+            let key = vault::current_key()?;
+            let username =
+                vault::encrypt_with(&key, &account.username, &vault::username_aad(account_id))?;
+            let password =
+                vault::encrypt_with(&key, &account.password, &vault::password_aad(account_id))?;
+
+            let mut conn = db.get()?;
+            let batch = conn.transaction()?;
+
+            let default_description = "这个人好懒,没有给他写备注信息呢┓(´∀`)┏".to_string();
+            let updated = batch.execute(
+                "UPDATE account SET name = ?, username = ?, password = ?, sequence = ?, liked = ?, description = ? WHERE id = ?",
+                params![
+                    account.name,
+                    username,
+                    password,
+                    account.sequence,
+                    account.liked,
+                    if account.description.is_none() || account.description.clone().unwrap().is_empty() { default_description } else { account.description.clone().unwrap() },
+                    account.id,
+                ],
+            )?;
+            if updated == 0 {
+                return Err(AppError::NotFound("要修改的账号不存在".to_string()));
+            }
 
-        batch.execute(
-            "DELETE FROM account_category WHERE account_id = ?",
-            params![account.id],
-        )?;
+            batch.execute(
+                "DELETE FROM account_category WHERE account_id = ?",
+                params![account.id],
+            )?;
 
-        if let Some(account_category_ids) = &account.account_category_ids {
-            for category_id in account_category_ids {
-                batch.execute(
-                    "INSERT INTO account_category (account_id, category_id, last_update_time)
-                        VALUES (?, ?, datetime('now'))",
-                    params![account.id, category_id],
-                )?;
+            if let Some(account_category_ids) = &account.account_category_ids {
+                for category_id in account_category_ids {
+                    batch.execute(
+                        "INSERT INTO account_category (account_id, category_id, last_update_time)
+                            VALUES (?, ?, datetime('now'))",
+                        params![account.id, category_id],
+                    )?;
+                }
             }
-        }
 
-        batch.execute("DELETE FROM role WHERE account_id = ?", params![account.id])?;
-        insert_roles_in_tx(&batch, account.id.unwrap(), account.roles.as_ref())?;
+            sync_roles_in_tx(&batch, account_id, account.roles.as_ref())?;
 
-        batch.commit()?;
+            batch.commit()?;
+        }
     }
 
     Ok(())
 }
 
-pub(crate) fn like_account(id: i32, liked: bool) -> Result<()> {
-    let conn = DB_CONNECTION.lock().unwrap();
-    conn.execute(
+pub(crate) fn like_account(db: &Database, id: i32, liked: bool) -> AppResult<()> {
+    vault::ensure_unlocked()?;
+    let conn = db.get()?;
+    let updated = conn.execute(
         "UPDATE account SET liked = ? WHERE id = ?",
         params![liked, id],
     )?;
+    if updated == 0 {
+        return Err(AppError::NotFound(format!("账号不存在: {id}")));
+    }
 
     Ok(())
 }
 
-pub(crate) fn delete_by_id(id: i32) -> Result<()> {
-    let conn = &mut DB_CONNECTION.lock().unwrap();
+pub(crate) fn delete_by_id(db: &Database, id: i32) -> AppResult<()> {
+    vault::ensure_unlocked()?;
+    snapshot::snapshot_before(db, SnapshotReason::Delete);
+    let mut conn = db.get()?;
     let batch = conn.transaction()?;
 
+    let role_ids = batch
+        .prepare("SELECT id FROM role WHERE account_id = ?")?
+        .query_map(params![id], |row| row.get::<_, i32>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    delete_role_dependents_with_conn(&batch, &role_ids)?;
     batch.execute("DELETE FROM role WHERE account_id = ?", params![id])?;
     batch.execute("DELETE FROM account WHERE id = ?", params![id])?;
 
@@ -556,95 +1031,1659 @@ pub(crate) fn delete_by_id(id: i32) -> Result<()> {
     Ok(())
 }
 
-pub(crate) fn query_all_accounts() -> Result<Vec<Account>> {
-    let conn = DB_CONNECTION.lock().unwrap();
-    let mut stmt = conn.prepare("
-        SELECT
-            a.id,
-            a.name,
-            a.username,
-            a.password,
-            a.sequence,
-            a.liked,
-            a.description,
-            a.last_update_time,
-            ac.account_category_ids
-        FROM account a
-        LEFT JOIN (
-            SELECT account_id, GROUP_CONCAT(category_id) AS account_category_ids
-            FROM account_category
-            GROUP BY account_id
-        ) AS ac ON a.id = ac.account_id
-        ORDER BY a.sequence ASC, a.id ASC"
-    )?;
-
-    attach_roles_to_accounts(&conn, _do_query_accounts(&mut stmt, &[])?)
+pub(crate) fn query_account_roles(db: &Database, account_id: i32) -> AppResult<Vec<Role>> {
+    vault::ensure_unlocked()?;
+    let conn = db.get()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ROLE_COLUMNS} FROM role r WHERE r.account_id = ? ORDER BY r.id ASC"
+    ))?;
+    let roles = stmt
+        .query_map(params![account_id], map_role)?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(roles)
 }
 
-pub(crate) fn query_accounts_by_value(
-    account: &Account,
-    with_liked: bool,
-    category_id: i32,
-) -> Result<Vec<Account>> {
-    let mut query = "
-        SELECT
-            a.id,
-            a.name,
-            a.username,
-            a.password,
-            a.sequence,
-            a.liked,
-            a.description,
-            a.last_update_time,
-            ac.account_category_ids
-        FROM account a
-        LEFT JOIN (
-            SELECT account_id, GROUP_CONCAT(category_id) AS account_category_ids
-            FROM account_category
-            GROUP BY account_id
-        ) AS ac ON a.id = ac.account_id
-        WHERE 1 = 1
-    "
-    .to_string();
-
-    let mut sub_queries = Vec::new();
-    let mut params: Vec<(&str, &dyn ToSql)> = Vec::new();
+/// 新增或修改单个角色，返回保存后的角色。
+pub(crate) fn save_role(db: &Database, role: &Role) -> AppResult<Role> {
+    vault::ensure_unlocked()?;
+    let mut role = role.clone();
+    role.normalize();
+    role.validate()?;
+    let Some(account_id) = role.account_id else {
+        return Err(AppError::Validation("角色必须属于一个账号".to_string()));
+    };
 
-    if !account.name.is_empty() {
-        sub_queries.push("name LIKE '%' || :name || '%'");
-        params.push((":name", &account.name));
+    let conn = db.get()?;
+    let account_exists = conn
+        .query_row(
+            "SELECT 1 FROM account WHERE id = ?",
+            params![account_id],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    if !account_exists {
+        return Err(AppError::NotFound(format!("账号不存在: {account_id}")));
     }
 
-    if !account.username.is_empty() {
-        sub_queries.push("username LIKE '%' || :username || '%'");
-        params.push((":username", &account.username));
-    }
+    let id = match role.id {
+        Some(id) => {
+            if update_role_with_conn(&conn, account_id, &role)? == 0 {
+                return Err(AppError::NotFound(format!("角色不存在: {id}")));
+            }
+            id
+        }
+        None => insert_role_with_conn(&conn, account_id, &role)?,
+    };
 
-    if !sub_queries.is_empty() {
-        query += &format!(" AND ( {} )", &sub_queries.join(" OR "));
-    }
+    Ok(conn.query_row(
+        &format!("SELECT {ROLE_COLUMNS} FROM role r WHERE r.id = ?"),
+        params![id],
+        map_role,
+    )?)
+}
 
-    if with_liked {
-        query += &format!(" AND liked = {}", account.liked.unwrap());
-    }
+/// 转义 LIKE 通配符，生成包含匹配的模式（配合 `ESCAPE '\'` 使用）。
+fn like_pattern(keyword: &str) -> String {
+    let escaped = keyword
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
 
-    if category_id > 0 {
+/// 去除首尾空白，空字符串视为未填写。
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+/// 读取 `ROLE_COLUMNS, a.name, s.zone, s.status`。
+fn map_role_search_result(row: &rusqlite::Row) -> Result<RoleSearchResult> {
+    Ok(RoleSearchResult {
+        role: map_role(row)?,
+        account_name: row.get(11)?,
+        zone: row.get(12)?,
+        status: row.get(13)?,
+    })
+}
+
+/// 跨账号搜索角色，按账号排序返回角色及所属账号。
+pub(crate) fn search_roles(db: &Database, query: &RoleQuery) -> AppResult<Vec<RoleSearchResult>> {
+    vault::ensure_unlocked()?;
+    let mut sql = format!(
+        "SELECT {ROLE_COLUMNS}, a.name, s.zone, s.status
+         FROM role r
+         JOIN account a ON a.id = r.account_id
+         LEFT JOIN jx3_server s ON s.server = r.server
+         WHERE 1 = 1"
+    );
+
+    let keyword = non_empty(query.keyword.as_deref()).map(like_pattern);
+    let mut params: Vec<(&str, &dyn ToSql)> = Vec::new();
+    if let Some(keyword) = &keyword {
+        sql += " AND (r.role_id LIKE :keyword ESCAPE '\\' OR r.name LIKE :keyword ESCAPE '\\')";
+        params.push((":keyword", keyword));
+    }
+    let server = non_empty(query.server.as_deref());
+    if let Some(server) = &server {
+        sql += " AND r.server = :server";
+        params.push((":server", server));
+    }
+    let zone = non_empty(query.zone.as_deref());
+    if let Some(zone) = &zone {
+        sql += " AND s.zone = :zone";
+        params.push((":zone", zone));
+    }
+    let school = non_empty(query.school.as_deref());
+    if let Some(school) = &school {
+        sql += " AND r.school = :school";
+        params.push((":school", school));
+    }
+    sql += " ORDER BY a.sequence ASC, a.id ASC, r.id ASC";
+
+    let conn = db.get()?;
+    let mut stmt = conn.prepare(&sql)?;
+    let results = stmt
+        .query_map(params.as_slice(), map_role_search_result)?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(results)
+}
+
+pub(crate) fn delete_role(db: &Database, id: i32) -> AppResult<()> {
+    vault::ensure_unlocked()?;
+    let mut conn = db.get()?;
+    let batch = conn.transaction()?;
+    delete_role_dependents_with_conn(&batch, &[id])?;
+    let deleted = batch.execute("DELETE FROM role WHERE id = ?", params![id])?;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("角色不存在: {id}")));
+    }
+    batch.commit()?;
+    Ok(())
+}
+
+/// 读取 `ROLE_TASK_COLUMNS`，任务列需位于查询结果最前面。
+fn map_role_task(row: &rusqlite::Row) -> Result<RoleTask> {
+    let period: String = row.get(2)?;
+    let period = TaskPeriod::parse(&period).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(
+            2,
+            Type::Text,
+            Box::new(AppError::Storage(format!("未知的任务周期: {period}"))),
+        )
+    })?;
+    Ok(RoleTask {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        period,
+        description: row.get(3)?,
+        sequence: row.get(4)?,
+        enabled: row.get(5)?,
+    })
+}
+
+const ROLE_TASK_COLUMNS: &str = "t.id, t.name, t.period, t.description, t.sequence, t.enabled";
+
+fn period_start(schedule: &ResetSchedule, period: TaskPeriod) -> i64 {
+    match period {
+        TaskPeriod::Daily => schedule.daily_start,
+        TaskPeriod::Weekly => schedule.weekly_start,
+    }
+}
+
+fn next_reset(schedule: &ResetSchedule, period: TaskPeriod) -> i64 {
+    match period {
+        TaskPeriod::Daily => schedule.next_daily_reset,
+        TaskPeriod::Weekly => schedule.next_weekly_reset,
+    }
+}
+
+pub(crate) fn get_task_reset_schedule() -> ResetSchedule {
+    ResetSchedule::at(unix_now())
+}
+
+pub(crate) fn query_role_tasks(db: &Database) -> AppResult<Vec<RoleTask>> {
+    let conn = db.get()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ROLE_TASK_COLUMNS} FROM role_task t ORDER BY t.sequence IS NULL, t.sequence ASC, t.id ASC"
+    ))?;
+    let tasks = stmt
+        .query_map([], map_role_task)?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(tasks)
+}
+
+/// 新增或修改任务模板，返回保存后的任务。
+pub(crate) fn save_role_task(db: &Database, task: &RoleTask) -> AppResult<RoleTask> {
+    task.validate()?;
+    let name = task.name.trim();
+    let description = non_empty(task.description.as_deref());
+
+    let conn = db.get()?;
+    let duplicate: Option<i32> = conn
+        .query_row(
+            "SELECT id FROM role_task WHERE name = ? AND id IS NOT ?",
+            params![name, task.id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if duplicate.is_some() {
+        return Err(AppError::Conflict(format!("任务已存在: {name}")));
+    }
+
+    let id = match task.id {
+        Some(id) => {
+            let updated = conn.execute(
+                "UPDATE role_task SET name = ?, period = ?, description = ?, sequence = ?, enabled = ?
+                 WHERE id = ?",
+                params![name, task.period.as_str(), description, task.sequence, task.enabled, id],
+            )?;
+            if updated == 0 {
+                return Err(AppError::NotFound(format!("任务不存在: {id}")));
+            }
+            id
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO role_task (name, period, description, sequence, enabled)
+                 VALUES (?, ?, ?, ?, ?)",
+                params![
+                    name,
+                    task.period.as_str(),
+                    description,
+                    task.sequence,
+                    task.enabled
+                ],
+            )?;
+            conn.last_insert_rowid() as i32
+        }
+    };
+
+    Ok(conn.query_row(
+        &format!("SELECT {ROLE_TASK_COLUMNS} FROM role_task t WHERE t.id = ?"),
+        params![id],
+        map_role_task,
+    )?)
+}
+
+/// 删除任务模板及其完成记录。
+pub(crate) fn delete_role_task(db: &Database, id: i32) -> AppResult<()> {
+    let mut conn = db.get()?;
+    let batch = conn.transaction()?;
+    batch.execute(
+        "DELETE FROM role_task_record WHERE task_id = ?",
+        params![id],
+    )?;
+    let deleted = batch.execute("DELETE FROM role_task WHERE id = ?", params![id])?;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("任务不存在: {id}")));
+    }
+    batch.commit()?;
+    Ok(())
+}
+
+/// 标记角色在当前周期内完成或未完成某个任务，返回完成时间。
+///
+/// `role_id` 为角色表主键。
+pub(crate) fn set_role_task_completed(
+    db: &Database,
+    role_id: i32,
+    task_id: i32,
+    completed: bool,
+) -> AppResult<Option<i64>> {
+    vault::ensure_unlocked()?;
+    let conn = db.get()?;
+    let task = conn
+        .query_row(
+            &format!("SELECT {ROLE_TASK_COLUMNS} FROM role_task t WHERE t.id = ?"),
+            params![task_id],
+            map_role_task,
+        )
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("任务不存在: {task_id}")))?;
+    ensure_role_exists(&conn, role_id)?;
+
+    let now = unix_now();
+    let start = period_start(&ResetSchedule::at(now), task.period);
+    if !completed {
+        conn.execute(
+            "DELETE FROM role_task_record WHERE role_id = ? AND task_id = ? AND period_start = ?",
+            params![role_id, task_id, start],
+        )?;
+        return Ok(None);
+    }
+
+    conn.execute(
+        "INSERT OR IGNORE INTO role_task_record (role_id, task_id, period_start, completed_at)
+         VALUES (?, ?, ?, ?)",
+        params![role_id, task_id, start, now],
+    )?;
+    Ok(conn.query_row(
+        "SELECT completed_at FROM role_task_record WHERE role_id = ? AND task_id = ? AND period_start = ?",
+        params![role_id, task_id, start],
+        |row| row.get(0),
+    )?)
+}
+
+/// 角色在当前周期内各启用任务的完成情况。
+pub(crate) fn query_role_task_progress(
+    db: &Database,
+    role_id: i32,
+) -> AppResult<Vec<RoleTaskProgress>> {
+    vault::ensure_unlocked()?;
+    let schedule = ResetSchedule::at(unix_now());
+    let conn = db.get()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ROLE_TASK_COLUMNS}, rec.completed_at
+         FROM role_task t
+         LEFT JOIN role_task_record rec
+            ON rec.task_id = t.id
+            AND rec.role_id = :role_id
+            AND rec.period_start = CASE t.period WHEN 'daily' THEN :daily_start ELSE :weekly_start END
+         WHERE t.enabled = 1
+         ORDER BY t.sequence IS NULL, t.sequence ASC, t.id ASC"
+    ))?;
+    let progress = stmt
+        .query_map(
+            rusqlite::named_params! {
+                ":role_id": role_id,
+                ":daily_start": schedule.daily_start,
+                ":weekly_start": schedule.weekly_start,
+            },
+            |row| {
+                let task = map_role_task(row)?;
+                Ok(RoleTaskProgress {
+                    reset_at: next_reset(&schedule, task.period),
+                    completed_at: row.get(6)?,
+                    task,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(progress)
+}
+
+/// 列出所有账号下各角色在当前周期内尚未完成的任务，`period` 为空时包括日常与周常。
+pub(crate) fn query_outstanding_tasks(
+    db: &Database,
+    period: Option<TaskPeriod>,
+) -> AppResult<Vec<OutstandingTask>> {
+    vault::ensure_unlocked()?;
+    let schedule = ResetSchedule::at(unix_now());
+    let conn = db.get()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ROLE_TASK_COLUMNS}, {ROLE_COLUMNS}, a.name
+         FROM role_task t
+         CROSS JOIN role r
+         JOIN account a ON a.id = r.account_id
+         WHERE t.enabled = 1
+            AND (:period IS NULL OR t.period = :period)
+            AND NOT EXISTS (
+                SELECT 1 FROM role_task_record rec
+                WHERE rec.role_id = r.id
+                    AND rec.task_id = t.id
+                    AND rec.period_start = CASE t.period WHEN 'daily' THEN :daily_start ELSE :weekly_start END
+            )
+         ORDER BY a.sequence ASC, a.id ASC, r.id ASC, t.sequence IS NULL, t.sequence ASC, t.id ASC"
+    ))?;
+    let tasks = stmt
+        .query_map(
+            rusqlite::named_params! {
+                ":period": period.map(TaskPeriod::as_str),
+                ":daily_start": schedule.daily_start,
+                ":weekly_start": schedule.weekly_start,
+            },
+            |row| {
+                let task = map_role_task(row)?;
+                Ok(OutstandingTask {
+                    task_id: task.id.unwrap_or_default(),
+                    reset_at: next_reset(&schedule, task.period),
+                    period: task.period,
+                    task_name: task.name,
+                    role: map_role_at(row, 6)?,
+                    account_name: row.get(17)?,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(tasks)
+}
+
+const RAID_COLUMNS: &str = "i.id, i.name, i.mode, i.sequence, i.enabled";
+
+/// 读取 `RAID_COLUMNS`，副本列需位于查询结果最前面。
+fn map_raid_instance(row: &rusqlite::Row) -> Result<RaidInstance> {
+    let mode: String = row.get(2)?;
+    Ok(RaidInstance {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        mode: (!mode.is_empty()).then_some(mode),
+        sequence: row.get(3)?,
+        enabled: row.get(4)?,
+    })
+}
+
+/// 读取 `RAID_COLUMNS` 之后的 `l.role_id, l.locked_at, l.expires_at`。
+fn map_raid_lockout(row: &rusqlite::Row) -> Result<RaidLockout> {
+    let raid = map_raid_instance(row)?;
+    Ok(RaidLockout {
+        raid_id: raid.id.unwrap_or_default(),
+        raid_name: raid.display_name(),
+        role_id: row.get(5)?,
+        locked_at: row.get(6)?,
+        expires_at: row.get(7)?,
+    })
+}
+
+fn find_raid_instance(conn: &Connection, id: i32) -> AppResult<RaidInstance> {
+    conn.query_row(
+        &format!("SELECT {RAID_COLUMNS} FROM raid_instance i WHERE i.id = ?"),
+        params![id],
+        map_raid_instance,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::NotFound(format!("副本不存在: {id}")))
+}
+
+fn ensure_role_exists(conn: &Connection, role_id: i32) -> AppResult<()> {
+    let exists = conn
+        .query_row("SELECT 1 FROM role WHERE id = ?", params![role_id], |_| {
+            Ok(())
+        })
+        .optional()?
+        .is_some();
+    if !exists {
+        return Err(AppError::NotFound(format!("角色不存在: {role_id}")));
+    }
+    Ok(())
+}
+
+pub(crate) fn query_raid_instances(db: &Database) -> AppResult<Vec<RaidInstance>> {
+    let conn = db.get()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {RAID_COLUMNS} FROM raid_instance i ORDER BY i.sequence IS NULL, i.sequence ASC, i.id ASC"
+    ))?;
+    let raids = stmt
+        .query_map([], map_raid_instance)?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(raids)
+}
+
+/// 新增或修改副本，返回保存后的副本。
+pub(crate) fn save_raid_instance(db: &Database, raid: &RaidInstance) -> AppResult<RaidInstance> {
+    raid.validate()?;
+    let name = raid.name.trim();
+    let mode = non_empty(raid.mode.as_deref()).unwrap_or_default();
+
+    let conn = db.get()?;
+    let duplicate: Option<i32> = conn
+        .query_row(
+            "SELECT id FROM raid_instance WHERE name = ? AND mode = ? AND id IS NOT ?",
+            params![name, mode, raid.id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if duplicate.is_some() {
+        return Err(AppError::Conflict(format!(
+            "副本已存在: {}",
+            raid.display_name().trim()
+        )));
+    }
+
+    let id = match raid.id {
+        Some(id) => {
+            let updated = conn.execute(
+                "UPDATE raid_instance SET name = ?, mode = ?, sequence = ?, enabled = ? WHERE id = ?",
+                params![name, mode, raid.sequence, raid.enabled, id],
+            )?;
+            if updated == 0 {
+                return Err(AppError::NotFound(format!("副本不存在: {id}")));
+            }
+            id
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO raid_instance (name, mode, sequence, enabled) VALUES (?, ?, ?, ?)",
+                params![name, mode, raid.sequence, raid.enabled],
+            )?;
+            conn.last_insert_rowid() as i32
+        }
+    };
+
+    find_raid_instance(&conn, id)
+}
+
+/// 删除副本及其 CD 记录。
+pub(crate) fn delete_raid_instance(db: &Database, id: i32) -> AppResult<()> {
+    let mut conn = db.get()?;
+    let batch = conn.transaction()?;
+    batch.execute("DELETE FROM raid_lockout WHERE raid_id = ?", params![id])?;
+    let deleted = batch.execute("DELETE FROM raid_instance WHERE id = ?", params![id])?;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("副本不存在: {id}")));
+    }
+    batch.commit()?;
+    Ok(())
+}
+
+/// 记录角色获得副本 CD，`locked_at` 为空时使用当前时间。
+///
+/// CD 在获得后的下一次周常刷新时解除；`role_id` 为角色表主键。
+pub(crate) fn record_raid_lockout(
+    db: &Database,
+    role_id: i32,
+    raid_id: i32,
+    locked_at: Option<i64>,
+) -> AppResult<RaidLockout> {
+    vault::ensure_unlocked()?;
+    let now = unix_now();
+    let locked_at = locked_at.unwrap_or(now);
+    if locked_at > now {
+        return Err(AppError::Validation(
+            "获得 CD 的时间不能晚于当前时间".to_string(),
+        ));
+    }
+    let expires_at = jx3_reset::next_weekly_reset(locked_at);
+    if expires_at <= now {
+        return Err(AppError::Validation(
+            "该时间获得的 CD 已随周常刷新解除".to_string(),
+        ));
+    }
+
+    let conn = db.get()?;
+    let raid = find_raid_instance(&conn, raid_id)?;
+    ensure_role_exists(&conn, role_id)?;
+    conn.execute(
+        "INSERT INTO raid_lockout (role_id, raid_id, locked_at, expires_at)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(role_id, raid_id, expires_at) DO UPDATE SET locked_at = excluded.locked_at",
+        params![role_id, raid_id, locked_at, expires_at],
+    )?;
+
+    Ok(RaidLockout {
+        raid_id,
+        raid_name: raid.display_name(),
+        role_id,
+        locked_at,
+        expires_at,
+    })
+}
+
+/// 清除角色本周的副本 CD（如误记）。
+pub(crate) fn clear_raid_lockout(db: &Database, role_id: i32, raid_id: i32) -> AppResult<()> {
+    vault::ensure_unlocked()?;
+    let conn = db.get()?;
+    let deleted = conn.execute(
+        "DELETE FROM raid_lockout WHERE role_id = ? AND raid_id = ? AND expires_at > ?",
+        params![role_id, raid_id, unix_now()],
+    )?;
+    if deleted == 0 {
+        return Err(AppError::NotFound("该角色本周没有此副本的 CD".to_string()));
+    }
+    Ok(())
+}
+
+/// 角色当前尚未解除的副本 CD。
+pub(crate) fn query_role_lockouts(db: &Database, role_id: i32) -> AppResult<Vec<RaidLockout>> {
+    vault::ensure_unlocked()?;
+    let conn = db.get()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {RAID_COLUMNS}, l.role_id, l.locked_at, l.expires_at
+         FROM raid_lockout l
+         JOIN raid_instance i ON i.id = l.raid_id
+         WHERE l.role_id = ? AND l.expires_at > ?
+         ORDER BY i.sequence IS NULL, i.sequence ASC, i.id ASC"
+    ))?;
+    let lockouts = stmt
+        .query_map(params![role_id, unix_now()], map_raid_lockout)?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(lockouts)
+}
+
+/// 所有账号下尚未解除的副本 CD，`raid_id` 为空时列出全部副本。
+pub(crate) fn query_raid_lockouts(
+    db: &Database,
+    raid_id: Option<i32>,
+) -> AppResult<Vec<RaidLockoutDetail>> {
+    vault::ensure_unlocked()?;
+    let conn = db.get()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {RAID_COLUMNS}, l.role_id, l.locked_at, l.expires_at, {ROLE_COLUMNS}, a.name
+         FROM raid_lockout l
+         JOIN raid_instance i ON i.id = l.raid_id
+         JOIN role r ON r.id = l.role_id
+         JOIN account a ON a.id = r.account_id
+         WHERE l.expires_at > :now AND (:raid_id IS NULL OR l.raid_id = :raid_id)
+         ORDER BY i.sequence IS NULL, i.sequence ASC, i.id ASC, a.sequence ASC, a.id ASC, r.id ASC"
+    ))?;
+    let lockouts = stmt
+        .query_map(
+            rusqlite::named_params! { ":now": unix_now(), ":raid_id": raid_id },
+            |row| {
+                Ok(RaidLockoutDetail {
+                    lockout: map_raid_lockout(row)?,
+                    role: map_role_at(row, 8)?,
+                    account_name: row.get(19)?,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(lockouts)
+}
+
+/// 本周还能打某个副本的角色：所有账号下没有该副本未解除 CD 的角色。
+pub(crate) fn query_raid_available_roles(
+    db: &Database,
+    raid_id: i32,
+) -> AppResult<Vec<RoleSearchResult>> {
+    vault::ensure_unlocked()?;
+    let conn = db.get()?;
+    find_raid_instance(&conn, raid_id)?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ROLE_COLUMNS}, a.name, s.zone, s.status
+         FROM role r
+         JOIN account a ON a.id = r.account_id
+         LEFT JOIN jx3_server s ON s.server = r.server
+         WHERE NOT EXISTS (
+            SELECT 1 FROM raid_lockout l
+            WHERE l.role_id = r.id AND l.raid_id = :raid_id AND l.expires_at > :now
+         )
+         ORDER BY a.sequence ASC, a.id ASC, r.id ASC"
+    ))?;
+    let roles = stmt
+        .query_map(
+            rusqlite::named_params! { ":raid_id": raid_id, ":now": unix_now() },
+            map_role_search_result,
+        )?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(roles)
+}
+
+fn ensure_ledger_roles_exist(conn: &Connection, entry: &LedgerEntry) -> AppResult<()> {
+    ensure_role_exists(conn, entry.role_id)?;
+    if let Some(counterparty) = entry.counterparty_role_id {
+        ensure_role_exists(conn, counterparty)?;
+    }
+    Ok(())
+}
+
+fn insert_ledger_entry_with_conn(conn: &Connection, entry: &LedgerEntry) -> AppResult<i32> {
+    ensure_ledger_roles_exist(conn, entry)?;
+    conn.execute(
+        "INSERT INTO role_ledger (role_id, occurred_at, currency, amount, counterparty_role_id, note)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![
+            entry.role_id,
+            entry.occurred_at,
+            entry.currency.trim(),
+            entry.amount,
+            entry.counterparty_role_id,
+            non_empty(entry.note.as_deref()),
+        ],
+    )?;
+    Ok(conn.last_insert_rowid() as i32)
+}
+
+/// 新增或修改一笔账本记录，返回记录 id。
+pub(crate) fn save_ledger_entry(db: &Database, entry: &LedgerEntry) -> AppResult<i32> {
+    vault::ensure_unlocked()?;
+    entry.validate()?;
+    let conn = db.get()?;
+    match entry.id {
+        Some(id) => {
+            ensure_ledger_roles_exist(&conn, entry)?;
+            let updated = conn.execute(
+                "UPDATE role_ledger
+                 SET role_id = ?, occurred_at = ?, currency = ?, amount = ?, counterparty_role_id = ?, note = ?
+                 WHERE id = ?",
+                params![
+                    entry.role_id,
+                    entry.occurred_at,
+                    entry.currency.trim(),
+                    entry.amount,
+                    entry.counterparty_role_id,
+                    non_empty(entry.note.as_deref()),
+                    id,
+                ],
+            )?;
+            if updated == 0 {
+                return Err(AppError::NotFound(format!("账本记录不存在: {id}")));
+            }
+            Ok(id)
+        }
+        None => insert_ledger_entry_with_conn(&conn, entry),
+    }
+}
+
+pub(crate) fn delete_ledger_entry(db: &Database, id: i32) -> AppResult<()> {
+    vault::ensure_unlocked()?;
+    let conn = db.get()?;
+    let deleted = conn.execute("DELETE FROM role_ledger WHERE id = ?", params![id])?;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("账本记录不存在: {id}")));
+    }
+    Ok(())
+}
+
+/// 在两个角色之间转移货币或物品：转出方记一笔支出、转入方记一笔收入，互为对方角色。
+pub(crate) fn record_ledger_transfer(db: &Database, transfer: &LedgerTransfer) -> AppResult<()> {
+    vault::ensure_unlocked()?;
+    if transfer.amount <= 0 {
+        return Err(AppError::Validation("转移数量必须大于 0".to_string()));
+    }
+    let outgoing = LedgerEntry {
+        id: None,
+        role_id: transfer.from_role_id,
+        occurred_at: transfer.occurred_at.unwrap_or_else(unix_now),
+        currency: transfer.currency.clone(),
+        amount: -transfer.amount,
+        counterparty_role_id: Some(transfer.to_role_id),
+        note: transfer.note.clone(),
+    };
+    outgoing.validate()?;
+    let incoming = LedgerEntry {
+        role_id: transfer.to_role_id,
+        amount: transfer.amount,
+        counterparty_role_id: Some(transfer.from_role_id),
+        ..outgoing.clone()
+    };
+
+    let mut conn = db.get()?;
+    let batch = conn.transaction()?;
+    insert_ledger_entry_with_conn(&batch, &outgoing)?;
+    insert_ledger_entry_with_conn(&batch, &incoming)?;
+    batch.commit()?;
+    Ok(())
+}
+
+/// 按条件查询账本，按时间倒序返回，并附带每笔记录之后的角色与账号滚动余额。
+pub(crate) fn query_ledger(db: &Database, query: &LedgerQuery) -> AppResult<Vec<LedgerRecord>> {
+    vault::ensure_unlocked()?;
+    let conn = db.get()?;
+    // 滚动余额在过滤前计算，按角色筛选时账号余额仍包含同账号的其他角色
+    let mut stmt = conn.prepare(&format!(
+        "SELECT l.id, l.role_id, l.occurred_at, l.currency, l.amount, l.counterparty_role_id, l.note,
+                l.role_balance, l.account_balance, {ROLE_COLUMNS}, a.name, c.role_id, c.server, ca.name
+         FROM (
+            SELECT g.*,
+                SUM(g.amount) OVER (PARTITION BY g.role_id, g.currency ORDER BY g.occurred_at, g.id) AS role_balance,
+                SUM(g.amount) OVER (PARTITION BY o.account_id, g.currency ORDER BY g.occurred_at, g.id) AS account_balance
+            FROM role_ledger g
+            JOIN role o ON o.id = g.role_id
+         ) l
+         JOIN role r ON r.id = l.role_id
+         JOIN account a ON a.id = r.account_id
+         LEFT JOIN role c ON c.id = l.counterparty_role_id
+         LEFT JOIN account ca ON ca.id = c.account_id
+         WHERE (:account_id IS NULL OR r.account_id = :account_id)
+           AND (:role_id IS NULL OR l.role_id = :role_id)
+           AND (:currency IS NULL OR l.currency = :currency)
+         ORDER BY l.occurred_at DESC, l.id DESC"
+    ))?;
+    let records = stmt
+        .query_map(
+            rusqlite::named_params! {
+                ":account_id": query.account_id,
+                ":role_id": query.role_id,
+                ":currency": non_empty(query.currency.as_deref()),
+            },
+            |row| {
+                let counterparty_role_id: Option<i32> = row.get(5)?;
+                let counterparty_name: Option<String> = row.get(21)?;
+                let counterparty = match (counterparty_role_id, counterparty_name) {
+                    (Some(id), Some(role_id)) => Some(LedgerCounterparty {
+                        id,
+                        role_id,
+                        server: row.get(22)?,
+                        account_name: row.get(23)?,
+                    }),
+                    _ => None,
+                };
+                Ok(LedgerRecord {
+                    entry: LedgerEntry {
+                        id: row.get(0)?,
+                        role_id: row.get(1)?,
+                        occurred_at: row.get(2)?,
+                        currency: row.get(3)?,
+                        amount: row.get(4)?,
+                        counterparty_role_id,
+                        note: row.get(6)?,
+                    },
+                    role_balance: row.get(7)?,
+                    account_balance: row.get(8)?,
+                    role: map_role_at(row, 9)?,
+                    account_name: row.get(20)?,
+                    counterparty,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(records)
+}
+
+/// 各账号每种货币的当前余额及角色明细，`account_id` 为空时返回所有账号。
+pub(crate) fn query_ledger_balances(
+    db: &Database,
+    account_id: Option<i32>,
+) -> AppResult<Vec<AccountLedgerBalance>> {
+    vault::ensure_unlocked()?;
+    let conn = db.get()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ROLE_COLUMNS}, a.name, g.currency, SUM(g.amount)
+         FROM role_ledger g
+         JOIN role r ON r.id = g.role_id
+         JOIN account a ON a.id = r.account_id
+         WHERE :account_id IS NULL OR r.account_id = :account_id
+         GROUP BY g.role_id, g.currency
+         ORDER BY a.sequence ASC, a.id ASC, g.currency ASC, r.id ASC"
+    ))?;
+    let rows = stmt
+        .query_map(
+            rusqlite::named_params! { ":account_id": account_id },
+            |row| {
+                Ok((
+                    map_role(row)?,
+                    row.get::<_, String>(11)?,
+                    row.get::<_, String>(12)?,
+                    row.get::<_, i64>(13)?,
+                ))
+            },
+        )?
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut balances: Vec<AccountLedgerBalance> = Vec::new();
+    for (role, account_name, currency, balance) in rows {
+        let account_id = role.account_id.unwrap_or_default();
+        let same_group = balances
+            .last()
+            .is_some_and(|last| last.account_id == account_id && last.currency == currency);
+        if !same_group {
+            balances.push(AccountLedgerBalance {
+                account_id,
+                account_name,
+                currency,
+                balance: 0,
+                roles: Vec::new(),
+            });
+        }
+        if let Some(group) = balances.last_mut() {
+            group.balance += balance;
+            group.roles.push(RoleLedgerBalance { role, balance });
+        }
+    }
+    Ok(balances)
+}
+
+/// 导出完整账号库，账号的用户名与密码解密为明文。已解除的副本 CD 不导出。
+pub(crate) fn export_vault(db: &Database) -> AppResult<VaultDocument> {
+    // 分类关联统一放在 account_categories 中
+    let accounts = query_all_accounts(db)?
+        .into_iter()
+        .map(|account| Account {
+            account_category_ids: None,
+            ..account
+        })
+        .collect();
+    let categories = query_all_categories(db)?
+        .into_iter()
+        .map(|category| Category {
+            account_ids: None,
+            ..category
+        })
+        .collect();
+    let role_tasks = query_role_tasks(db)?;
+    let raids = query_raid_instances(db)?;
+    let server_merges = query_jx3_server_merges(db)?;
+
+    let conn = db.get()?;
+    let account_categories = conn
+        .prepare("SELECT id, account_id, category_id, last_update_time FROM account_category ORDER BY id ASC")?
+        .query_map([], |row| {
+            Ok(AccountCategory {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                group_id: row.get(2)?,
+                last_update_time: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut settings = std::collections::BTreeMap::new();
+    for key in TRANSFERABLE_SETTING_KEYS {
+        if let Some(value) = get_setting_with_conn(&conn, key)? {
+            settings.insert(key.to_string(), value);
+        }
+    }
+
+    let task_records = conn
+        .prepare("SELECT role_id, task_id, period_start, completed_at FROM role_task_record ORDER BY id ASC")?
+        .query_map([], |row| {
+            Ok(TaskRecordExport {
+                role_id: row.get(0)?,
+                task_id: row.get(1)?,
+                period_start: row.get(2)?,
+                completed_at: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let raid_lockouts = conn
+        .prepare(
+            "SELECT role_id, raid_id, locked_at, expires_at FROM raid_lockout
+             WHERE expires_at > ? ORDER BY id ASC",
+        )?
+        .query_map(params![unix_now()], |row| {
+            Ok(RaidLockoutExport {
+                role_id: row.get(0)?,
+                raid_id: row.get(1)?,
+                locked_at: row.get(2)?,
+                expires_at: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let ledger = conn
+        .prepare(
+            "SELECT id, role_id, occurred_at, currency, amount, counterparty_role_id, note
+             FROM role_ledger ORDER BY occurred_at ASC, id ASC",
+        )?
+        .query_map([], |row| {
+            Ok(LedgerEntry {
+                id: row.get(0)?,
+                role_id: row.get(1)?,
+                occurred_at: row.get(2)?,
+                currency: row.get(3)?,
+                amount: row.get(4)?,
+                counterparty_role_id: row.get(5)?,
+                note: row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let watched_servers = conn
+        .prepare("SELECT server FROM jx3_server WHERE watched = 1 ORDER BY server ASC")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>>>()?;
+
+    Ok(VaultDocument {
+        format: VAULT_DOCUMENT_FORMAT.to_string(),
+        version: VAULT_DOCUMENT_VERSION,
+        app_version: APP_VERSION.to_string(),
+        exported_at: unix_now(),
+        accounts,
+        categories,
+        account_categories,
+        settings,
+        role_tasks,
+        task_records,
+        raids,
+        raid_lockouts,
+        ledger,
+        server_merges,
+        watched_servers,
+    })
+}
+
+/// 把导出文档合并进当前账号库，所有数据在一个事务内写入。
+pub(crate) fn import_vault(
+    db: &Database,
+    document: &VaultDocument,
+) -> AppResult<VaultTransferSummary> {
+    write_vault_document(db, document, false)
+}
+
+/// 清空当前账号库后写入导出文档，清空与写入在同一个事务内，任何一步失败都保持原样。
+///
+/// 不在导出范围内的设置（主密码校验信息、存储位置、同步缓存等）保持不变。
+pub(crate) fn replace_vault(
+    db: &Database,
+    document: &VaultDocument,
+) -> AppResult<VaultTransferSummary> {
+    write_vault_document(db, document, true)
+}
+
+fn write_vault_document(
+    db: &Database,
+    document: &VaultDocument,
+    replace: bool,
+) -> AppResult<VaultTransferSummary> {
+    let key = vault::current_key()?;
+    snapshot::snapshot_before(db, SnapshotReason::Import);
+    let mut conn = db.get()?;
+    let batch = conn.transaction()?;
+    if replace {
+        clear_vault_with_conn(&batch)?;
+    }
+    let summary = import_vault_with_conn(&batch, &key, document)?;
+    batch.commit()?;
+
+    if document.settings.contains_key(VAULT_IDLE_TIMEOUT_KEY) {
+        vault::set_idle_timeout(get_vault_idle_timeout(db)?);
+    }
+    Ok(summary)
+}
+
+/// 删除账号、分类、角色及其日常周常、副本 CD、账本，以及合服记录与关注的区服。
+fn clear_vault_with_conn(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        "DELETE FROM role_task_record;
+         DELETE FROM raid_lockout;
+         DELETE FROM role_ledger;
+         DELETE FROM role_task;
+         DELETE FROM raid_instance;
+         DELETE FROM role;
+         DELETE FROM account_category;
+         DELETE FROM account;
+         DELETE FROM category;
+         DELETE FROM jx3_server_merge;
+         UPDATE jx3_server SET watched = 0;",
+    )?;
+    Ok(())
+}
+
+/// 在调用方的事务内写入导出文档。
+///
+/// 账号总是新增；分类、任务、副本按名称与已有数据合并。导出文件中的 id 重新分配，
+/// 关联数据按新旧 id 的对应关系改写，引用不到的记录跳过并计入 `skipped`。
+fn import_vault_with_conn(
+    batch: &Connection,
+    key: &VaultKey,
+    document: &VaultDocument,
+) -> AppResult<VaultTransferSummary> {
+    let mut summary = VaultTransferSummary::default();
+
+    let mut category_ids = HashMap::new();
+    let category_base: i32 =
+        batch.query_row("SELECT IFNULL(MAX(sequence), 0) FROM category", [], |row| {
+            row.get(0)
+        })?;
+    for (index, category) in document.categories.iter().enumerate() {
+        let name = category.name.trim();
+        if name.is_empty() {
+            summary.skipped += 1;
+            continue;
+        }
+        let existing: Option<i32> = batch
+            .query_row(
+                "SELECT id FROM category WHERE name = ?",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let id = match existing {
+            Some(id) => id,
+            None => {
+                batch.execute(
+                    "INSERT INTO category (name, sequence, last_update_time) VALUES (?, ?, datetime('now'))",
+                    params![name, category_base + category.sequence.unwrap_or(index as i32 + 1)],
+                )?;
+                summary.categories += 1;
+                batch.last_insert_rowid() as i32
+            }
+        };
+        if let Some(old_id) = category.id {
+            category_ids.insert(old_id, id);
+        }
+    }
+
+    let mut account_ids = HashMap::new();
+    let mut role_ids = HashMap::new();
+    let account_base: i32 =
+        batch.query_row("SELECT IFNULL(MAX(sequence), 0) FROM account", [], |row| {
+            row.get(0)
+        })?;
+    for (index, account) in document.accounts.iter().enumerate() {
+        if account.name.trim().is_empty() {
+            summary.skipped += 1;
+            continue;
+        }
+        let roles = prepare_roles(account.roles.as_ref())?;
+        batch.execute(
+            "INSERT INTO account (name, username, password, sequence, liked, description, last_update_time)
+             VALUES (?, '', '', ?, ?, ?, datetime('now'))",
+            params![
+                account.name.trim(),
+                account_base + account.sequence.unwrap_or(index as i32 + 1),
+                account.liked.unwrap_or(false),
+                account.description,
+            ],
+        )?;
+        let account_id = batch.last_insert_rowid() as i32;
+        write_credentials_with_conn(batch, key, account_id, &account.username, &account.password)?;
+        if let Some(old_id) = account.id {
+            account_ids.insert(old_id, account_id);
+        }
+        for role in &roles {
+            let role_id = insert_role_with_conn(batch, account_id, role)?;
+            if let Some(old_id) = role.id {
+                role_ids.insert(old_id, role_id);
+            }
+        }
+        summary.accounts += 1;
+        summary.roles += roles.len();
+    }
+
+    for link in &document.account_categories {
+        match (
+            account_ids.get(&link.account_id),
+            category_ids.get(&link.group_id),
+        ) {
+            (Some(account_id), Some(category_id)) => {
+                batch.execute(
+                    "INSERT INTO account_category (account_id, category_id, last_update_time)
+                     SELECT ?1, ?2, datetime('now')
+                     WHERE NOT EXISTS (SELECT 1 FROM account_category WHERE account_id = ?1 AND category_id = ?2)",
+                    params![account_id, category_id],
+                )?;
+            }
+            _ => summary.skipped += 1,
+        }
+    }
+
+    let mut task_ids = HashMap::new();
+    for task in &document.role_tasks {
+        if task.validate().is_err() {
+            summary.skipped += 1;
+            continue;
+        }
+        let name = task.name.trim();
+        let existing: Option<i32> = batch
+            .query_row(
+                "SELECT id FROM role_task WHERE name = ?",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let id = match existing {
+            Some(id) => id,
+            None => {
+                batch.execute(
+                    "INSERT INTO role_task (name, period, description, sequence, enabled)
+                     VALUES (?, ?, ?, ?, ?)",
+                    params![
+                        name,
+                        task.period.as_str(),
+                        non_empty(task.description.as_deref()),
+                        task.sequence,
+                        task.enabled,
+                    ],
+                )?;
+                summary.role_tasks += 1;
+                batch.last_insert_rowid() as i32
+            }
+        };
+        if let Some(old_id) = task.id {
+            task_ids.insert(old_id, id);
+        }
+    }
+
+    for record in &document.task_records {
+        match (role_ids.get(&record.role_id), task_ids.get(&record.task_id)) {
+            (Some(role_id), Some(task_id)) => {
+                batch.execute(
+                    "INSERT OR IGNORE INTO role_task_record (role_id, task_id, period_start, completed_at)
+                     VALUES (?, ?, ?, ?)",
+                    params![role_id, task_id, record.period_start, record.completed_at],
+                )?;
+            }
+            _ => summary.skipped += 1,
+        }
+    }
+
+    let mut raid_ids = HashMap::new();
+    for raid in &document.raids {
+        if raid.validate().is_err() {
+            summary.skipped += 1;
+            continue;
+        }
+        let name = raid.name.trim();
+        let mode = non_empty(raid.mode.as_deref()).unwrap_or_default();
+        let existing: Option<i32> = batch
+            .query_row(
+                "SELECT id FROM raid_instance WHERE name = ? AND mode = ?",
+                params![name, mode],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let id = match existing {
+            Some(id) => id,
+            None => {
+                batch.execute(
+                    "INSERT INTO raid_instance (name, mode, sequence, enabled) VALUES (?, ?, ?, ?)",
+                    params![name, mode, raid.sequence, raid.enabled],
+                )?;
+                summary.raids += 1;
+                batch.last_insert_rowid() as i32
+            }
+        };
+        if let Some(old_id) = raid.id {
+            raid_ids.insert(old_id, id);
+        }
+    }
+
+    for lockout in &document.raid_lockouts {
+        match (
+            role_ids.get(&lockout.role_id),
+            raid_ids.get(&lockout.raid_id),
+        ) {
+            (Some(role_id), Some(raid_id)) => {
+                batch.execute(
+                    "INSERT OR IGNORE INTO raid_lockout (role_id, raid_id, locked_at, expires_at)
+                     VALUES (?, ?, ?, ?)",
+                    params![role_id, raid_id, lockout.locked_at, lockout.expires_at],
+                )?;
+            }
+            _ => summary.skipped += 1,
+        }
+    }
+
+    for entry in &document.ledger {
+        let Some(role_id) = role_ids.get(&entry.role_id) else {
+            summary.skipped += 1;
+            continue;
+        };
+        if entry.validate().is_err() {
+            summary.skipped += 1;
+            continue;
+        }
+        let counterparty = entry.counterparty_role_id.and_then(|id| role_ids.get(&id));
+        batch.execute(
+            "INSERT INTO role_ledger (role_id, occurred_at, currency, amount, counterparty_role_id, note)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                role_id,
+                entry.occurred_at,
+                entry.currency.trim(),
+                entry.amount,
+                counterparty,
+                non_empty(entry.note.as_deref()),
+            ],
+        )?;
+        summary.ledger_entries += 1;
+    }
+
+    let merges: Vec<Jx3ServerMerge> = document
+        .server_merges
+        .iter()
+        .filter(|merge| merge.validate().is_ok())
+        .cloned()
+        .collect();
+    summary.skipped += document.server_merges.len() - merges.len();
+    upsert_jx3_server_merges_with_conn(batch, &merges)?;
+
+    for server in &document.watched_servers {
+        batch.execute(
+            "UPDATE jx3_server SET watched = 1 WHERE server = ?",
+            params![server],
+        )?;
+    }
+
+    for (key, value) in &document.settings {
+        let value = match validate_transferable_setting(key, value) {
+            Ok(value) => value,
+            Err(_) => {
+                summary.skipped += 1;
+                continue;
+            }
+        };
+        // 更换数据来源后旧的缓存校验信息不再适用
+        if key == JX3_PROVIDER_KEY
+            && get_setting_with_conn(batch, key)?.as_deref() != Some(value.as_str())
+        {
+            clear_jx3_http_cache_with_conn(batch)?;
+        }
+        set_setting_with_conn(batch, key, &value)?;
+        summary.settings += 1;
+    }
+
+    Ok(summary)
+}
+
+/// 按各设置项保存时的校验规则检查导入的值，返回规范化后的值；不可迁移的设置项或不合法的值返回错误。
+fn validate_transferable_setting(key: &str, value: &str) -> AppResult<String> {
+    let invalid = || AppError::Validation(format!("设置项 {key} 的值无效: {value}"));
+    let number = |value: &str| value.trim().parse::<u64>().map_err(|_| invalid());
+    match key {
+        "hide_username" | "hide_password" | "network_sync_enabled" | "network_sync_prompted" => {
+            match value {
+                "0" | "1" => Ok(value.to_string()),
+                _ => Err(invalid()),
+            }
+        }
+        "window_width" | "window_height" => value
+            .trim()
+            .parse::<u32>()
+            .map(|size| size.to_string())
+            .map_err(|_| invalid()),
+        "favorite_filter" => value
+            .trim()
+            .parse::<i32>()
+            .map(|filter| filter.to_string())
+            .map_err(|_| invalid()),
+        "export_fields" => {
+            let fields: Vec<String> = serde_json::from_str(value).map_err(|_| invalid())?;
+            Ok(serde_json::to_string(&fields)?)
+        }
+        VAULT_IDLE_TIMEOUT_KEY | CLIPBOARD_CLEAR_SECONDS_KEY => {
+            number(value).map(|secs| secs.to_string())
+        }
+        SNAPSHOT_RETENTION_KEY => {
+            let count = u32::try_from(number(value)?).map_err(|_| invalid())?;
+            validate_snapshot_retention(count)?;
+            Ok(count.to_string())
+        }
+        JX3_SYNC_INTERVAL_KEY => {
+            let secs = number(value)?;
+            validate_jx3_sync_interval(secs)?;
+            Ok(secs.to_string())
+        }
+        JX3_PROVIDER_KEY => {
+            let config: ProviderConfig = serde_json::from_str(value).map_err(|_| invalid())?;
+            config.validate()?;
+            Ok(serde_json::to_string(&config)?)
+        }
+        JX3_MAINTENANCE_STATUS_KEY => normalize_jx3_maintenance_status(value),
+        _ => Err(invalid()),
+    }
+}
+
+/// 判断重复用的键：按 `by` 取名称与用户名，只比较用户名时名称为空；用户名为空的账号不参与判断。
+fn duplicate_key(by: CsvDuplicateKey, name: &str, username: &str) -> Option<(String, String)> {
+    if username.is_empty() {
+        return None;
+    }
+    let name = match by {
+        CsvDuplicateKey::Username => "",
+        CsvDuplicateKey::NameAndUsername => name,
+    };
+    Some((name.to_string(), username.to_string()))
+}
+
+/// 导入一行 CSV 账号，返回处理结果与账号 id（跳过时为 `None`）；出错时由调用方回滚该行。
+fn import_csv_row(
+    conn: &Connection,
+    key: &VaultKey,
+    row: &CsvAccountRow,
+    duplicate: CsvDuplicateStrategy,
+    existing: Option<i32>,
+    category_ids: &mut HashMap<String, i32>,
+    report: &mut CsvImportReport,
+) -> AppResult<(CsvRowAction, Option<i32>)> {
+    let username = row.username.as_deref().unwrap_or_default();
+
+    let (account_id, action) = match (existing, duplicate) {
+        (Some(_), CsvDuplicateStrategy::Skip) => return Ok((CsvRowAction::Skipped, None)),
+        (Some(id), CsvDuplicateStrategy::Overwrite) => {
+            conn.execute(
+                "UPDATE account SET name = ? WHERE id = ?",
+                params![row.name, id],
+            )?;
+            if let Some(password) = &row.password {
+                conn.execute(
+                    "UPDATE account SET password = ? WHERE id = ?",
+                    params![
+                        vault::encrypt_with(key, password, &vault::password_aad(id))?,
+                        id
+                    ],
+                )?;
+            }
+            if let Some(description) = &row.description {
+                conn.execute(
+                    "UPDATE account SET description = ? WHERE id = ?",
+                    params![description, id],
+                )?;
+            }
+            (id, CsvRowAction::Updated)
+        }
+        _ => {
+            conn.execute(
+                "INSERT INTO account (name, username, password, sequence, liked, description, last_update_time)
+                 VALUES (?, '', '', (SELECT IFNULL(MAX(sequence), 0) + 1 FROM account), 0, ?, datetime('now'))",
+                params![row.name, row.description],
+            )?;
+            let id = conn.last_insert_rowid() as i32;
+            write_credentials_with_conn(
+                conn,
+                key,
+                id,
+                username,
+                row.password.as_deref().unwrap_or_default(),
+            )?;
+            (id, CsvRowAction::Created)
+        }
+    };
+
+    for name in &row.categories {
+        let category_id = match category_ids.get(name) {
+            Some(id) => *id,
+            None => {
+                conn.execute(
+                    "INSERT INTO category (name, sequence, last_update_time)
+                     VALUES (?, (SELECT IFNULL(MAX(sequence), 0) + 1 FROM category), datetime('now'))",
+                    params![name],
+                )?;
+                let id = conn.last_insert_rowid() as i32;
+                category_ids.insert(name.clone(), id);
+                report.categories_created += 1;
+                id
+            }
+        };
+        conn.execute(
+            "INSERT INTO account_category (account_id, category_id, last_update_time)
+             SELECT ?1, ?2, datetime('now')
+             WHERE NOT EXISTS (SELECT 1 FROM account_category WHERE account_id = ?1 AND category_id = ?2)",
+            params![account_id, category_id],
+        )?;
+    }
+
+    for (role_id, server) in &row.roles {
+        let exists = conn
+            .query_row(
+                "SELECT 1 FROM role WHERE account_id = ? AND role_id = ? AND server = ?",
+                params![account_id, role_id, server],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if exists {
+            continue;
+        }
+        let role = Role {
+            id: None,
+            account_id: Some(account_id),
+            role_id: role_id.clone(),
+            server: server.clone(),
+            name: None,
+            school: None,
+            body_type: None,
+            level: None,
+            equipment_score: None,
+            notes: None,
+            last_update_time: None,
+        };
+        role.validate()?;
+        insert_role_with_conn(conn, account_id, &role)?;
+    }
+
+    Ok((action, Some(account_id)))
+}
+
+/// 导入 CSV 解析出的账号。每行在独立的保存点中执行，单行失败只回滚该行；
+/// `dry_run` 为 `true` 时最终回滚整个事务，只返回报告。
+///
+/// 已有账号按 `duplicate_by` 指定的字段（用户名解密后比较）判断是否重复。
+pub(crate) fn import_csv_accounts(
+    db: &Database,
+    rows: &[CsvAccountRow],
+    duplicate_by: CsvDuplicateKey,
+    duplicate: CsvDuplicateStrategy,
+    report: &mut CsvImportReport,
+) -> AppResult<()> {
+    let key = vault::current_key()?;
+    if !report.dry_run {
+        snapshot::snapshot_before(db, SnapshotReason::Import);
+    }
+    let mut conn = db.get()?;
+    let mut batch = conn.transaction()?;
+
+    let mut existing_ids = HashMap::new();
+    {
+        let mut stmt = batch.prepare("SELECT id, name, username FROM account ORDER BY id ASC")?;
+        let existing = stmt
+            .query_map([], |row| {
+                let id = row.get::<_, i32>(0)?;
+                Ok((
+                    id,
+                    row.get::<_, String>(1)?,
+                    decrypt_column(&key, row, 2, &vault::username_aad(id))?,
+                ))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        for (id, name, username) in existing {
+            if let Some(existing_key) = duplicate_key(duplicate_by, &name, &username) {
+                existing_ids.entry(existing_key).or_insert(id);
+            }
+        }
+    }
+    let mut category_ids: HashMap<String, i32> = batch
+        .prepare("SELECT name, id FROM category ORDER BY id DESC")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_>>()?;
+
+    for row in rows {
+        let savepoint = batch.savepoint()?;
+        // 失败时分类可能已加入缓存，回滚后需要恢复
+        let categories_before = category_ids.clone();
+        let created_before = report.categories_created;
+        let row_key = duplicate_key(
+            duplicate_by,
+            &row.name,
+            row.username.as_deref().unwrap_or_default(),
+        );
+        let existing = row_key
+            .as_ref()
+            .and_then(|row_key| existing_ids.get(row_key).copied());
+        let result = import_csv_row(
+            &savepoint,
+            &key,
+            row,
+            duplicate,
+            existing,
+            &mut category_ids,
+            report,
+        );
+        match result {
+            Ok((CsvRowAction::Skipped, _)) => {
+                savepoint.commit()?;
+                let message = format!(
+                    "账号已存在: {}",
+                    row.username.as_deref().unwrap_or_default()
+                );
+                report.push(row.line, &row.name, CsvRowAction::Skipped, Some(message));
+            }
+            Ok((action, account_id)) => {
+                savepoint.commit()?;
+                // 同一文件中后出现的相同账号也按重复处理
+                if let (Some(row_key), Some(id)) = (row_key, account_id) {
+                    existing_ids.entry(row_key).or_insert(id);
+                }
+                report.push(row.line, &row.name, action, None);
+            }
+            Err(err) => {
+                // 未提交的保存点在释放时回滚
+                drop(savepoint);
+                category_ids = categories_before;
+                report.categories_created = created_before;
+                report.push(
+                    row.line,
+                    &row.name,
+                    CsvRowAction::Failed,
+                    Some(err.message().to_string()),
+                );
+            }
+        }
+    }
+
+    if !report.dry_run {
+        batch.commit()?;
+    }
+    Ok(())
+}
+
+pub(crate) fn query_all_accounts(db: &Database) -> AppResult<Vec<Account>> {
+    let key = vault::current_key()?;
+    let conn = db.get()?;
+    let mut stmt = conn.prepare(
+        "
+        SELECT
+            a.id,
+            a.name,
+            a.username,
+            a.password,
+            a.sequence,
+            a.liked,
+            a.description,
+            a.last_update_time,
+            ac.account_category_ids
+        FROM account a
+        LEFT JOIN (
+            SELECT account_id, GROUP_CONCAT(category_id) AS account_category_ids
+            FROM account_category
+            GROUP BY account_id
+        ) AS ac ON a.id = ac.account_id
+        ORDER BY a.sequence ASC, a.id ASC",
+    )?;
+
+    Ok(attach_roles_to_accounts(
+        &conn,
+        _do_query_accounts(&mut stmt, &[], &key)?,
+    )?)
+}
+
+/// 按条件查询账号；`role_server` / `role_zone` 只保留在该区服或大区有角色的账号。
+pub(crate) fn query_accounts_by_value(
+    db: &Database,
+    account: &Account,
+    with_liked: bool,
+    category_id: i32,
+    role_server: Option<&str>,
+    role_zone: Option<&str>,
+) -> AppResult<Vec<Account>> {
+    let key = vault::current_key()?;
+    let mut query = "
+        SELECT
+            a.id,
+            a.name,
+            a.username,
+            a.password,
+            a.sequence,
+            a.liked,
+            a.description,
+            a.last_update_time,
+            ac.account_category_ids
+        FROM account a
+        LEFT JOIN (
+            SELECT account_id, GROUP_CONCAT(category_id) AS account_category_ids
+            FROM account_category
+            GROUP BY account_id
+        ) AS ac ON a.id = ac.account_id
+        WHERE 1 = 1
+    "
+    .to_string();
+
+    let mut params: Vec<(&str, &dyn ToSql)> = Vec::new();
+
+    // 用户名已加密存储，无法在 SQL 中匹配，名称与用户名的关键字过滤统一在解密后进行
+    let name_keyword = account.name.to_lowercase();
+    let username_keyword = account.username.to_lowercase();
+
+    if with_liked {
+        query += &format!(" AND liked = {}", account.liked.unwrap());
+    }
+
+    if category_id > 0 {
         query +=
             " AND id IN (SELECT account_id FROM account_category WHERE category_id = :category_id)";
         params.push((":category_id", &category_id));
     }
 
+    let role_server = non_empty(role_server);
+    if let Some(role_server) = &role_server {
+        query += " AND a.id IN (SELECT account_id FROM role WHERE server = :role_server)";
+        params.push((":role_server", role_server));
+    }
+
+    let role_zone = non_empty(role_zone);
+    if let Some(role_zone) = &role_zone {
+        query += " AND a.id IN (
+            SELECT r.account_id FROM role r
+            JOIN jx3_server s ON s.server = r.server
+            WHERE s.zone = :role_zone
+        )";
+        params.push((":role_zone", role_zone));
+    }
+
     query += " ORDER BY a.sequence ASC, a.id ASC";
 
-    let conn = DB_CONNECTION.lock().unwrap();
+    let conn = db.get()?;
     let mut stmt = conn.prepare(&query)?;
 
-    attach_roles_to_accounts(&conn, _do_query_accounts(&mut stmt, &params)?)
+    let mut accounts = _do_query_accounts(&mut stmt, &params, &key)?;
+    if !name_keyword.is_empty() || !username_keyword.is_empty() {
+        accounts.retain(|item| {
+            (!name_keyword.is_empty() && item.name.to_lowercase().contains(&name_keyword))
+                || (!username_keyword.is_empty()
+                    && item.username.to_lowercase().contains(&username_keyword))
+        });
+    }
+
+    Ok(attach_roles_to_accounts(&conn, accounts)?)
 }
 
 fn _do_query_accounts(
     stmt: &mut rusqlite::Statement,
     params: &[(&str, &dyn ToSql)],
+    key: &VaultKey,
 ) -> Result<Vec<Account>> {
     let rows = stmt.query_map(params, |row| {
         let account_category_ids: Result<Option<String>> = row.get(8);
@@ -656,11 +2695,12 @@ fn _do_query_accounts(
             _ => Some(Vec::new()),
         };
 
+        let id: i32 = row.get(0)?;
         Ok(Account {
-            id: row.get(0)?,
+            id: Some(id),
             name: row.get(1)?,
-            username: row.get(2)?,
-            password: row.get(3)?,
+            username: decrypt_column(key, row, 2, &vault::username_aad(id))?,
+            password: decrypt_column(key, row, 3, &vault::password_aad(id))?,
             sequence: row.get(4)?,
             liked: row.get(5)?,
             description: row.get(6)?,
@@ -678,8 +2718,26 @@ fn _do_query_accounts(
     Ok(accounts)
 }
 
-pub(crate) fn create_category(category: &Category) -> Result<()> {
-    let conn = &mut DB_CONNECTION.lock().unwrap();
+/// 读取并解密单个账号的敏感字段。
+pub(crate) fn get_account_field(db: &Database, id: i32, field: AccountField) -> AppResult<String> {
+    let key = vault::current_key()?;
+    let (column, aad) = match field {
+        AccountField::Username => ("username", vault::username_aad(id)),
+        AccountField::Password => ("password", vault::password_aad(id)),
+    };
+
+    let conn = db.get()?;
+    let mut stmt = conn.prepare(&format!("SELECT {column} FROM account WHERE id = ?"))?;
+    let mut rows = stmt.query(params![id])?;
+    match rows.next()? {
+        Some(row) => Ok(decrypt_column(&key, row, 0, &aad)?),
+        None => Err(AppError::NotFound(format!("账号不存在: {id}"))),
+    }
+}
+
+pub(crate) fn create_category(db: &Database, category: &Category) -> AppResult<()> {
+    vault::ensure_unlocked()?;
+    let mut conn = db.get()?;
     conn.execute(
         "INSERT INTO category (name, sequence, last_update_time)
         VALUES (?, IFNULL(?, 1), datetime('now'))",
@@ -705,9 +2763,11 @@ pub(crate) fn create_category(category: &Category) -> Result<()> {
     Ok(())
 }
 
-pub(crate) fn query_all_categories() -> Result<Vec<Category>> {
-    let conn = DB_CONNECTION.lock().unwrap();
-    let mut stmt = conn.prepare("
+pub(crate) fn query_all_categories(db: &Database) -> AppResult<Vec<Category>> {
+    vault::ensure_unlocked()?;
+    let conn = db.get()?;
+    let mut stmt = conn.prepare(
+        "
         SELECT
             c.id,
             c.name,
@@ -722,7 +2782,8 @@ pub(crate) fn query_all_categories() -> Result<Vec<Category>> {
         ) AS ac ON c.id = ac.category_id
         WHERE 1 = 1
         ORDER BY c.sequence ASC, c.id ASC
-    ")?;
+    ",
+    )?;
 
     let rows = stmt.query_map([], |row| {
         let account_category_ids: Result<Option<String>> = row.get(4);
@@ -751,17 +2812,21 @@ pub(crate) fn query_all_categories() -> Result<Vec<Category>> {
     Ok(categories)
 }
 
-pub(crate) fn update_category(category: &Category) -> Result<()> {
+pub(crate) fn update_category(db: &Database, category: &Category) -> AppResult<()> {
+    vault::ensure_unlocked()?;
     if category.id.is_none() {
-        create_category(category)?;
+        create_category(db, category)?;
     } else {
-        let conn =&mut DB_CONNECTION.lock().unwrap();
+        let mut conn = db.get()?;
         let batch = conn.transaction()?;
 
-        batch.execute(
+        let updated = batch.execute(
             "UPDATE category SET name = ?, sequence = ? WHERE id = ?",
             params![category.name, category.sequence, category.id],
         )?;
+        if updated == 0 {
+            return Err(AppError::NotFound("要修改的分组不存在".to_string()));
+        }
 
         batch.execute(
             "DELETE FROM account_category WHERE category_id = ?",
@@ -784,8 +2849,10 @@ pub(crate) fn update_category(category: &Category) -> Result<()> {
     Ok(())
 }
 
-pub(crate) fn delete_category_by_id(id: i32) -> Result<()> {
-    let conn = &mut DB_CONNECTION.lock().unwrap();
+pub(crate) fn delete_category_by_id(db: &Database, id: i32) -> AppResult<()> {
+    vault::ensure_unlocked()?;
+    snapshot::snapshot_before(db, SnapshotReason::Delete);
+    let mut conn = db.get()?;
     let batch = conn.transaction()?;
     // delete account_category first
     batch.execute(
@@ -800,8 +2867,9 @@ pub(crate) fn delete_category_by_id(id: i32) -> Result<()> {
     Ok(())
 }
 
-pub(crate) fn reorder_accounts(ids: &[i32]) -> Result<()> {
-    let conn = &mut DB_CONNECTION.lock().unwrap();
+pub(crate) fn reorder_accounts(db: &Database, ids: &[i32]) -> AppResult<()> {
+    vault::ensure_unlocked()?;
+    let mut conn = db.get()?;
     let batch = conn.transaction()?;
 
     for (index, id) in ids.iter().enumerate() {
@@ -816,8 +2884,9 @@ pub(crate) fn reorder_accounts(ids: &[i32]) -> Result<()> {
     Ok(())
 }
 
-pub(crate) fn reorder_categories(ids: &[i32]) -> Result<()> {
-    let conn = &mut DB_CONNECTION.lock().unwrap();
+pub(crate) fn reorder_categories(db: &Database, ids: &[i32]) -> AppResult<()> {
+    vault::ensure_unlocked()?;
+    let mut conn = db.get()?;
     let batch = conn.transaction()?;
 
     for (index, id) in ids.iter().enumerate() {
@@ -832,197 +2901,824 @@ pub(crate) fn reorder_categories(ids: &[i32]) -> Result<()> {
     Ok(())
 }
 
-pub(crate) fn get_setting(key: &str) -> Result<Option<String>> {
-    let conn = DB_CONNECTION.lock().unwrap();
-    let mut stmt = conn.prepare("SELECT value FROM app_setting WHERE key = ?1")?;
-    let mut rows = stmt.query(params![key])?;
-    if let Some(row) = rows.next()? {
-        Ok(Some(row.get(0)?))
-    } else {
-        Ok(None)
+pub(crate) fn get_setting(db: &Database, key: &str) -> AppResult<Option<String>> {
+    let conn = db.get()?;
+    let mut stmt = conn.prepare("SELECT value FROM app_setting WHERE key = ?1")?;
+    let mut rows = stmt.query(params![key])?;
+    if let Some(row) = rows.next()? {
+        Ok(Some(row.get(0)?))
+    } else {
+        Ok(None)
+    }
+}
+
+pub(crate) fn set_setting(db: &Database, key: &str, value: &str) -> AppResult<()> {
+    let conn = db.get()?;
+    conn.execute(
+        "INSERT INTO app_setting (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn get_window_size(db: &Database) -> AppResult<Option<(u32, u32)>> {
+    let width = get_setting(db, "window_width")?.and_then(|value| value.parse().ok());
+    let height = get_setting(db, "window_height")?.and_then(|value| value.parse().ok());
+
+    match (width, height) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => Ok(Some((width, height))),
+        _ => Ok(None),
+    }
+}
+
+pub(crate) fn save_window_size(db: &Database, width: u32, height: u32) -> AppResult<()> {
+    set_setting(db, "window_width", &width.to_string())?;
+    set_setting(db, "window_height", &height.to_string())?;
+    Ok(())
+}
+
+pub(crate) fn get_display_settings(db: &Database) -> AppResult<(bool, bool)> {
+    let hide_username = get_setting(db, "hide_username")?
+        .map(|value| value == "1")
+        .unwrap_or(false);
+    let hide_password = get_setting(db, "hide_password")?
+        .map(|value| value == "1")
+        .unwrap_or(true);
+
+    Ok((hide_username, hide_password))
+}
+
+pub(crate) fn save_display_settings(
+    db: &Database,
+    hide_username: bool,
+    hide_password: bool,
+) -> AppResult<()> {
+    set_setting(db, "hide_username", if hide_username { "1" } else { "0" })?;
+    set_setting(db, "hide_password", if hide_password { "1" } else { "0" })?;
+    Ok(())
+}
+
+pub(crate) fn get_app_version(db: &Database) -> AppResult<String> {
+    Ok(get_stored_db_version(&*db.get()?)?)
+}
+
+pub(crate) fn get_current_app_version() -> &'static str {
+    APP_VERSION
+}
+
+/// 角色表的查询列，查询时角色表需使用别名 `r`。
+const ROLE_COLUMNS: &str = "r.id, r.account_id, r.role_id, r.server, r.name, r.school, r.body_type,
+    r.level, r.equipment_score, r.notes, r.last_update_time";
+
+fn map_role(row: &rusqlite::Row) -> Result<Role> {
+    map_role_at(row, 0)
+}
+
+/// 从第 `offset` 列开始读取 `ROLE_COLUMNS`，用于与其他表联查的结果。
+fn map_role_at(row: &rusqlite::Row, offset: usize) -> Result<Role> {
+    Ok(Role {
+        id: row.get(offset)?,
+        account_id: row.get(offset + 1)?,
+        role_id: row.get(offset + 2)?,
+        server: row.get(offset + 3)?,
+        name: row.get(offset + 4)?,
+        school: row.get(offset + 5)?,
+        body_type: row.get(offset + 6)?,
+        level: row.get(offset + 7)?,
+        equipment_score: row.get(offset + 8)?,
+        notes: row.get(offset + 9)?,
+        last_update_time: row.get(offset + 10)?,
+    })
+}
+
+fn insert_role_with_conn(conn: &Connection, account_id: i32, role: &Role) -> Result<i32> {
+    conn.execute(
+        "INSERT INTO role (account_id, role_id, server, name, school, body_type, level, equipment_score, notes, last_update_time)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, datetime('now'))",
+        params![
+            account_id,
+            role.role_id,
+            role.server,
+            role.name,
+            role.school,
+            role.body_type,
+            role.level,
+            role.equipment_score,
+            role.notes,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid() as i32)
+}
+
+/// 更新角色信息，返回受影响的行数；角色不属于 `account_id` 时不会更新。
+fn update_role_with_conn(conn: &Connection, account_id: i32, role: &Role) -> Result<usize> {
+    conn.execute(
+        "UPDATE role SET role_id = ?, server = ?, name = ?, school = ?, body_type = ?, level = ?,
+            equipment_score = ?, notes = ?, last_update_time = datetime('now')
+         WHERE id = ? AND account_id = ?",
+        params![
+            role.role_id,
+            role.server,
+            role.name,
+            role.school,
+            role.body_type,
+            role.level,
+            role.equipment_score,
+            role.notes,
+            role.id,
+            account_id,
+        ],
+    )
+}
+
+/// 整理随账号提交的角色列表：跳过空行并校验其余角色。
+fn prepare_roles(roles: Option<&Vec<Role>>) -> AppResult<Vec<Role>> {
+    let mut prepared = Vec::new();
+    for role in roles.into_iter().flatten() {
+        let mut role = role.clone();
+        role.normalize();
+        if role.is_blank() {
+            continue;
+        }
+        role.validate()?;
+        prepared.push(role);
+    }
+    Ok(prepared)
+}
+
+fn insert_roles_in_tx(
+    batch: &rusqlite::Transaction,
+    account_id: i32,
+    roles: Option<&Vec<Role>>,
+) -> AppResult<()> {
+    for role in prepare_roles(roles)? {
+        insert_role_with_conn(batch, account_id, &role)?;
+    }
+    Ok(())
+}
+
+/// 按角色 id 同步账号的角色列表：已有角色原地更新，新角色插入，列表中没有的角色删除。
+///
+/// 原地更新可保留角色 id，避免关联到角色的数据失效。`roles` 为 `None` 时不改动角色。
+fn sync_roles_in_tx(
+    batch: &rusqlite::Transaction,
+    account_id: i32,
+    roles: Option<&Vec<Role>>,
+) -> AppResult<()> {
+    if roles.is_none() {
+        return Ok(());
+    }
+
+    let mut kept = HashSet::new();
+    for role in prepare_roles(roles)? {
+        match role.id {
+            Some(id) => {
+                if update_role_with_conn(batch, account_id, &role)? == 0 {
+                    return Err(AppError::NotFound(format!("角色不存在: {id}")));
+                }
+                kept.insert(id);
+            }
+            None => {
+                kept.insert(insert_role_with_conn(batch, account_id, &role)?);
+            }
+        }
+    }
+
+    let mut stmt = batch.prepare("SELECT id FROM role WHERE account_id = ?")?;
+    let existing = stmt
+        .query_map(params![account_id], |row| row.get::<_, i32>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    let removed: Vec<i32> = existing
+        .into_iter()
+        .filter(|id| !kept.contains(id))
+        .collect();
+    delete_role_dependents_with_conn(batch, &removed)?;
+    for id in removed {
+        batch.execute("DELETE FROM role WHERE id = ?", params![id])?;
+    }
+    Ok(())
+}
+
+/// 删除挂在角色上的数据（任务完成记录、副本 CD、账本等），在删除角色前调用。
+///
+/// 其他角色账本中以该角色为对方的记录保留，只清空对方角色。
+fn delete_role_dependents_with_conn(conn: &Connection, role_ids: &[i32]) -> Result<()> {
+    for id in role_ids {
+        conn.execute(
+            "DELETE FROM role_task_record WHERE role_id = ?",
+            params![id],
+        )?;
+        conn.execute("DELETE FROM raid_lockout WHERE role_id = ?", params![id])?;
+        conn.execute("DELETE FROM role_ledger WHERE role_id = ?", params![id])?;
+        conn.execute(
+            "UPDATE role_ledger SET counterparty_role_id = NULL WHERE counterparty_role_id = ?",
+            params![id],
+        )?;
+    }
+    Ok(())
+}
+
+fn load_all_roles_map(conn: &Connection) -> Result<HashMap<i32, Vec<Role>>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ROLE_COLUMNS} FROM role r ORDER BY r.id ASC"
+    ))?;
+    let rows = stmt.query_map([], map_role)?;
+
+    let mut map: HashMap<i32, Vec<Role>> = HashMap::new();
+    for row in rows {
+        let role = row?;
+        if let Some(account_id) = role.account_id {
+            map.entry(account_id).or_default().push(role);
+        }
+    }
+    Ok(map)
+}
+
+fn attach_roles_to_accounts(conn: &Connection, mut accounts: Vec<Account>) -> Result<Vec<Account>> {
+    if accounts.is_empty() {
+        return Ok(accounts);
+    }
+    let roles_map = load_all_roles_map(conn)?;
+    for account in &mut accounts {
+        if let Some(id) = account.id {
+            account.roles = Some(roles_map.get(&id).cloned().unwrap_or_default());
+        } else {
+            account.roles = Some(Vec::new());
+        }
+    }
+    Ok(accounts)
+}
+
+/// 迁移时写入兜底区服，只依赖 1.1.4 的表结构。
+fn seed_jx3_servers_with_conn(conn: &Connection, servers: &[Jx3Server]) -> Result<()> {
+    for server in servers {
+        conn.execute(
+            "INSERT INTO jx3_server (zone, server, status, last_update_time)
+             VALUES (?1, ?2, ?3, datetime('now'))
+             ON CONFLICT(server) DO UPDATE SET
+                zone = excluded.zone,
+                status = excluded.status,
+                last_update_time = datetime('now')",
+            params![server.zone, server.server, server.status],
+        )?;
+    }
+    Ok(())
+}
+
+fn map_jx3_server(row: &rusqlite::Row) -> Result<Jx3Server> {
+    Ok(Jx3Server {
+        id: row.get(0)?,
+        zone: row.get(1)?,
+        server: row.get(2)?,
+        status: row.get(3)?,
+        last_update_time: row.get(4)?,
+        watched: row.get(5)?,
+    })
+}
+
+/// 写入区服列表，返回新增或状态、大区发生变化的区服（含写入后的 id 与更新时间）。
+///
+/// `record_history` 为 true 时把与最近一条历史记录不同的状态记入状态历史；
+/// 兜底数据不是实际观测到的状态，写入时不应记录。
+pub(crate) fn upsert_jx3_servers_with_conn(
+    conn: &Connection,
+    servers: &[Jx3Server],
+    record_history: bool,
+) -> Result<Vec<Jx3ServerChange>> {
+    let mut select_stmt = conn.prepare("SELECT zone, status FROM jx3_server WHERE server = ?1")?;
+    let mut upsert_stmt = conn.prepare(
+        "INSERT INTO jx3_server (zone, server, status, last_update_time)
+         VALUES (?1, ?2, ?3, datetime('now'))
+         ON CONFLICT(server) DO UPDATE SET
+            zone = excluded.zone,
+            status = excluded.status,
+            last_update_time = datetime('now')
+         RETURNING id, zone, server, status, last_update_time, watched",
+    )?;
+
+    let mut last_history_stmt = conn.prepare(
+        "SELECT status FROM jx3_server_status_history WHERE server = ?1
+         ORDER BY changed_at DESC, id DESC LIMIT 1",
+    )?;
+    let mut history_stmt = conn.prepare(
+        "INSERT INTO jx3_server_status_history (server, zone, status, changed_at)
+         VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    let now = unix_now();
+
+    let mut changed = Vec::new();
+    for server in servers {
+        let previous: Option<(String, String)> = select_stmt
+            .query_row(params![server.server], |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional()?;
+        let current = upsert_stmt.query_row(
+            params![server.zone, server.server, server.status],
+            map_jx3_server,
+        )?;
+
+        let status_changed = previous
+            .as_ref()
+            .is_none_or(|(_, status)| *status != current.status);
+        // 兜底数据会覆盖 jx3_server 中的状态，是否变化以最近一条观测记录为准
+        if record_history {
+            let last_observed: Option<String> = last_history_stmt
+                .query_row(params![current.server], |row| row.get(0))
+                .optional()?;
+            if last_observed.as_deref() != Some(current.status.as_str()) {
+                history_stmt.execute(params![current.server, current.zone, current.status, now])?;
+            }
+        }
+
+        match previous {
+            Some((zone, _)) if zone == current.zone && !status_changed => {}
+            previous => changed.push(Jx3ServerChange {
+                previous_status: previous.map(|(_, status)| status),
+                current,
+            }),
+        }
+    }
+    Ok(changed)
+}
+
+pub(crate) fn query_all_jx3_servers(db: &Database) -> AppResult<Vec<Jx3Server>> {
+    let conn = db.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, zone, server, status, last_update_time, watched FROM jx3_server ORDER BY zone ASC, server ASC",
+    )?;
+    let rows = stmt.query_map([], map_jx3_server)?;
+
+    let mut servers = Vec::new();
+    for row in rows {
+        servers.push(row?);
+    }
+    Ok(servers)
+}
+
+pub(crate) fn set_jx3_server_watched(db: &Database, server: &str, watched: bool) -> AppResult<()> {
+    let conn = db.get()?;
+    let updated = conn.execute(
+        "UPDATE jx3_server SET watched = ?1 WHERE server = ?2",
+        params![watched, server],
+    )?;
+    if updated == 0 {
+        return Err(AppError::NotFound(format!("区服不存在: {server}")));
+    }
+    Ok(())
+}
+
+fn ensure_jx3_server_exists(conn: &Connection, server: &str) -> AppResult<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM jx3_server WHERE server = ?1)",
+        params![server],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Err(AppError::NotFound(format!("区服不存在: {server}")));
     }
+    Ok(())
 }
 
-pub(crate) fn set_setting(key: &str, value: &str) -> Result<()> {
-    let conn = DB_CONNECTION.lock().unwrap();
-    conn.execute(
-        "INSERT INTO app_setting (key, value) VALUES (?1, ?2)
-         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
-        params![key, value],
+/// 读取 `(from, to]` 内的状态记录，并在最前面补上 `from` 时刻正在生效的那条记录。
+fn load_status_history(
+    conn: &Connection,
+    server: &str,
+    from: i64,
+    to: i64,
+) -> Result<Vec<Jx3ServerStatusRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT status, changed_at FROM (
+            SELECT status, changed_at FROM jx3_server_status_history
+            WHERE server = ?1 AND changed_at <= ?2
+            ORDER BY changed_at DESC, id DESC LIMIT 1
+         )
+         UNION ALL
+         SELECT status, changed_at FROM (
+            SELECT status, changed_at FROM jx3_server_status_history
+            WHERE server = ?1 AND changed_at > ?2 AND changed_at <= ?3
+            ORDER BY changed_at ASC, id ASC
+         )",
     )?;
-    Ok(())
+    let rows = stmt.query_map(params![server, from, to], |row| {
+        Ok(Jx3ServerStatusRecord {
+            status: row.get(0)?,
+            changed_at: row.get(1)?,
+        })
+    })?;
+
+    let mut records = Vec::new();
+    for row in rows {
+        records.push(row?);
+    }
+    Ok(records)
 }
 
-pub(crate) fn get_window_size() -> Result<Option<(u32, u32)>> {
-    let width = get_setting("window_width")?.and_then(|value| value.parse().ok());
-    let height = get_setting("window_height")?.and_then(|value| value.parse().ok());
+/// 查询区服状态时间线；`from`、`to` 缺省时分别为最早记录与当前时间。
+pub(crate) fn query_jx3_server_timeline(
+    db: &Database,
+    server: &str,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> AppResult<Vec<Jx3ServerStatusRecord>> {
+    let to = to.unwrap_or_else(unix_now);
+    let from = from.unwrap_or(i64::MIN);
+    if from > to {
+        return Err(AppError::Validation("开始时间不能晚于结束时间".to_string()));
+    }
 
-    match (width, height) {
-        (Some(width), Some(height)) if width > 0 && height > 0 => Ok(Some((width, height))),
-        _ => Ok(None),
+    let conn = db.get()?;
+    ensure_jx3_server_exists(&conn, server)?;
+    Ok(load_status_history(&conn, server, from, to)?)
+}
+
+/// 统计最近 `window_secs` 秒内各状态所占的时间与百分比，按时长从多到少排列。
+pub(crate) fn get_jx3_server_uptime(
+    db: &Database,
+    server: &str,
+    window_secs: u64,
+) -> AppResult<Jx3ServerUptime> {
+    if window_secs == 0 {
+        return Err(AppError::Validation("统计时长必须大于 0".to_string()));
+    }
+    let to = unix_now();
+    let from = to.saturating_sub(window_secs as i64);
+
+    let conn = db.get()?;
+    ensure_jx3_server_exists(&conn, server)?;
+    let records = load_status_history(&conn, server, from, to)?;
+
+    let mut durations: Vec<(String, i64)> = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        let start = record.changed_at.max(from);
+        let end = records.get(index + 1).map_or(to, |next| next.changed_at);
+        if end <= start {
+            continue;
+        }
+        match durations
+            .iter_mut()
+            .find(|(status, _)| *status == record.status)
+        {
+            Some((_, seconds)) => *seconds += end - start,
+            None => durations.push((record.status.clone(), end - start)),
+        }
     }
+
+    let observed_seconds: i64 = durations.iter().map(|(_, seconds)| seconds).sum();
+    durations.sort_by_key(|(_, seconds)| std::cmp::Reverse(*seconds));
+    let statuses = durations
+        .into_iter()
+        .map(|(status, seconds)| Jx3ServerStatusShare {
+            status,
+            seconds,
+            percentage: seconds as f64 * 100.0 / observed_seconds as f64,
+        })
+        .collect();
+
+    Ok(Jx3ServerUptime {
+        server: server.to_string(),
+        from,
+        to,
+        observed_seconds,
+        statuses,
+    })
 }
 
-pub(crate) fn save_window_size(width: u32, height: u32) -> Result<()> {
-    set_setting("window_width", &width.to_string())?;
-    set_setting("window_height", &height.to_string())?;
-    Ok(())
+/// 查询最近一次维护的开始时间，以及维护之后第一次变为其他状态（开服）的时间。
+pub(crate) fn get_jx3_server_last_maintenance(
+    db: &Database,
+    server: &str,
+) -> AppResult<Jx3ServerMaintenance> {
+    let maintenance_status = get_jx3_maintenance_status(db)?;
+    let conn = db.get()?;
+    ensure_jx3_server_exists(&conn, server)?;
+
+    let started_at: Option<i64> = conn.query_row(
+        "SELECT MAX(changed_at) FROM jx3_server_status_history WHERE server = ?1 AND status = ?2",
+        params![server, maintenance_status],
+        |row| row.get(0),
+    )?;
+    let ended_at: Option<i64> = match started_at {
+        Some(started_at) => conn.query_row(
+            "SELECT MIN(changed_at) FROM jx3_server_status_history
+             WHERE server = ?1 AND status <> ?2 AND changed_at > ?3",
+            params![server, maintenance_status, started_at],
+            |row| row.get(0),
+        )?,
+        None => None,
+    };
+
+    Ok(Jx3ServerMaintenance {
+        server: server.to_string(),
+        started_at,
+        ended_at,
+    })
 }
 
-pub(crate) fn get_display_settings() -> Result<(bool, bool)> {
-    let hide_username = get_setting("hide_username")?
-        .map(|value| value == "1")
-        .unwrap_or(false);
-    let hide_password = get_setting("hide_password")?
-        .map(|value| value == "1")
-        .unwrap_or(true);
+pub(crate) fn is_network_sync_enabled(db: &Database) -> AppResult<bool> {
+    Ok(get_setting(db, "network_sync_enabled")?
+        .map(|v| v == "1")
+        .unwrap_or(false))
+}
 
-    Ok((hide_username, hide_password))
+/// 在同一事务内写入区服列表并记录同步时间，返回发生变化的区服。
+/// 兜底区服列表（含合服记录）只在其版本更新时补充写入。
+///
+/// `observed` 表示数据来自联网获取，只有此时才记录状态历史。
+/// `cache` 为本次响应的缓存校验信息；为 `None`（如写入兜底数据）时清除旧的校验信息，
+/// 避免下次条件请求返回 304 而保留兜底状态。
+pub(crate) fn save_jx3_servers(
+    db: &Database,
+    servers: &[Jx3Server],
+    observed: bool,
+    cache: Option<&CacheValidators>,
+) -> AppResult<Vec<Jx3ServerChange>> {
+    let mut conn = db.get()?;
+    let batch = conn.transaction()?;
+    let changed = upsert_jx3_servers_with_conn(&batch, servers, observed)?;
+    match cache {
+        Some(cache) => {
+            set_setting_with_conn(&batch, JX3_HTTP_CACHE_KEY, &serde_json::to_string(cache)?)?
+        }
+        None => clear_jx3_http_cache_with_conn(&batch)?,
+    }
+    set_setting_with_conn(&batch, "jx3_server_last_sync", &chrono_lite_now())?;
+    batch.commit()?;
+    refresh_fallback_servers(&conn)?;
+    Ok(changed)
 }
 
-pub(crate) fn save_display_settings(hide_username: bool, hide_password: bool) -> Result<()> {
-    set_setting("hide_username", if hide_username { "1" } else { "0" })?;
-    set_setting("hide_password", if hide_password { "1" } else { "0" })?;
-    Ok(())
+/// 数据未变化（304）时只记录同步时间。
+pub(crate) fn touch_jx3_server_last_sync(db: &Database) -> AppResult<()> {
+    set_setting(db, "jx3_server_last_sync", &chrono_lite_now())
 }
 
-pub(crate) fn get_app_version() -> Result<String> {
-    get_stored_db_version(&DB_CONNECTION.lock().unwrap())
+/// 读取上次联网获取时保存的缓存校验信息，内容无法解析时视为没有缓存。
+pub(crate) fn get_jx3_http_cache(db: &Database) -> AppResult<Option<CacheValidators>> {
+    Ok(get_setting(db, JX3_HTTP_CACHE_KEY)?.and_then(|value| serde_json::from_str(&value).ok()))
 }
 
-pub(crate) fn get_current_app_version() -> &'static str {
-    APP_VERSION
+fn clear_jx3_http_cache_with_conn(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "DELETE FROM app_setting WHERE key = ?1",
+        params![JX3_HTTP_CACHE_KEY],
+    )?;
+    Ok(())
 }
 
-fn insert_roles_in_tx(
-    batch: &rusqlite::Transaction,
-    account_id: i32,
-    roles: Option<&Vec<Role>>,
-) -> Result<()> {
-    if let Some(roles) = roles {
-        for role in roles {
-            if role.role_id.trim().is_empty() || role.server.trim().is_empty() {
-                continue;
-            }
-            batch.execute(
-                "INSERT INTO role (account_id, role_id, server, last_update_time)
-                 VALUES (?, ?, ?, datetime('now'))",
-                params![account_id, role.role_id.trim(), role.server.trim()],
-            )?;
-        }
+/// 写入合服记录；合并目标变化时清除已应用标记，以便重新迁移角色。
+fn upsert_jx3_server_merges_with_conn(conn: &Connection, merges: &[Jx3ServerMerge]) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "INSERT INTO jx3_server_merge (old_server, new_server, effective_date)
+         VALUES (?1, ?2, ?3)
+         ON CONFLICT(old_server) DO UPDATE SET
+            new_server = excluded.new_server,
+            effective_date = excluded.effective_date,
+            applied_at = CASE
+                WHEN jx3_server_merge.new_server = excluded.new_server THEN jx3_server_merge.applied_at
+                ELSE NULL
+            END",
+    )?;
+    for merge in merges {
+        stmt.execute(params![
+            merge.old_server,
+            merge.new_server,
+            merge.effective_date
+        ])?;
     }
     Ok(())
 }
 
-fn load_all_roles_map(conn: &Connection) -> Result<std::collections::HashMap<i32, Vec<Role>>> {
+pub(crate) fn query_jx3_server_merges(db: &Database) -> AppResult<Vec<Jx3ServerMerge>> {
+    let conn = db.get()?;
     let mut stmt = conn.prepare(
-        "SELECT id, account_id, role_id, server, last_update_time FROM role ORDER BY id ASC",
+        "SELECT old_server, new_server, effective_date, applied_at FROM jx3_server_merge
+         ORDER BY effective_date DESC, old_server ASC",
     )?;
     let rows = stmt.query_map([], |row| {
-        Ok(Role {
-            id: row.get(0)?,
-            account_id: row.get(1)?,
-            role_id: row.get(2)?,
-            server: row.get(3)?,
-            last_update_time: row.get(4)?,
+        Ok(Jx3ServerMerge {
+            old_server: row.get(0)?,
+            new_server: row.get(1)?,
+            effective_date: row.get(2)?,
+            applied_at: row.get(3)?,
         })
     })?;
 
-    let mut map: std::collections::HashMap<i32, Vec<Role>> = std::collections::HashMap::new();
+    let mut merges = Vec::new();
     for row in rows {
-        let role = row?;
-        if let Some(account_id) = role.account_id {
-            map.entry(account_id).or_default().push(role);
-        }
+        merges.push(row?);
     }
-    Ok(map)
+    Ok(merges)
 }
 
-fn attach_roles_to_accounts(conn: &Connection, mut accounts: Vec<Account>) -> Result<Vec<Account>> {
-    if accounts.is_empty() {
-        return Ok(accounts);
+pub(crate) fn save_jx3_server_merge(db: &Database, merge: &Jx3ServerMerge) -> AppResult<()> {
+    merge.validate()?;
+    let merge = Jx3ServerMerge {
+        old_server: merge.old_server.trim().to_string(),
+        new_server: merge.new_server.trim().to_string(),
+        effective_date: merge.effective_date.trim().to_string(),
+        applied_at: None,
+    };
+    let conn = db.get()?;
+    upsert_jx3_server_merges_with_conn(&conn, &[merge])?;
+    Ok(())
+}
+
+pub(crate) fn delete_jx3_server_merge(db: &Database, old_server: &str) -> AppResult<()> {
+    let conn = db.get()?;
+    let deleted = conn.execute(
+        "DELETE FROM jx3_server_merge WHERE old_server = ?1",
+        params![old_server],
+    )?;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("合服记录不存在: {old_server}")));
     }
-    let roles_map = load_all_roles_map(conn)?;
-    for account in &mut accounts {
-        if let Some(id) = account.id {
-            account.roles = Some(roles_map.get(&id).cloned().unwrap_or_default());
-        } else {
-            account.roles = Some(Vec::new());
+    Ok(())
+}
+
+/// 沿合服链找到最终区服（如 A 并入 B、B 又并入 C 时 A 的结果为 C）。
+fn resolve_merge_target(targets: &HashMap<String, String>, server: &str) -> AppResult<String> {
+    let mut current = server;
+    let mut visited = HashSet::from([server]);
+    while let Some(next) = targets.get(current) {
+        if !visited.insert(next) {
+            return Err(AppError::Validation(format!("合服记录存在循环: {server}")));
         }
+        current = next;
     }
-    Ok(accounts)
+    Ok(current.to_string())
 }
 
-pub(crate) fn upsert_jx3_servers_with_conn(
-    conn: &Connection,
-    servers: &[Jx3Server],
-) -> Result<()> {
-    for server in servers {
-        conn.execute(
-            "INSERT INTO jx3_server (zone, server, status, last_update_time)
-             VALUES (?1, ?2, ?3, datetime('now'))
-             ON CONFLICT(server) DO UPDATE SET
-                zone = excluded.zone,
-                status = excluded.status,
-                last_update_time = datetime('now')",
-            params![server.zone, server.server, server.status],
+/// 把已生效的合服记录应用到角色：在一个事务内改写 `role.server`，并返回受影响的账号。
+///
+/// `dry_run` 为 true 时只生成报告，不写入数据库。
+pub(crate) fn apply_jx3_server_merges(db: &Database, dry_run: bool) -> AppResult<MergeReport> {
+    let mut conn = db.get()?;
+    let batch = conn.transaction()?;
+
+    let mut merges = Vec::new();
+    {
+        let mut stmt = batch.prepare(
+            "SELECT old_server, new_server, effective_date, applied_at FROM jx3_server_merge
+             WHERE effective_date <= date('now', 'localtime')
+             ORDER BY effective_date ASC, old_server ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Jx3ServerMerge {
+                old_server: row.get(0)?,
+                new_server: row.get(1)?,
+                effective_date: row.get(2)?,
+                applied_at: row.get(3)?,
+            })
+        })?;
+        for row in rows {
+            merges.push(row?);
+        }
+    }
+
+    let targets: HashMap<String, String> = merges
+        .iter()
+        .map(|merge| (merge.old_server.clone(), merge.new_server.clone()))
+        .collect();
+
+    let mut affected: Vec<AffectedAccount> = Vec::new();
+    let mut migrated_roles = 0;
+    {
+        let mut select_stmt = batch.prepare(
+            "SELECT r.account_id, a.name, r.role_id FROM role r
+             JOIN account a ON a.id = r.account_id
+             WHERE r.server = ?1
+             ORDER BY r.account_id ASC, r.id ASC",
         )?;
+        let mut update_stmt = batch.prepare("UPDATE role SET server = ?1 WHERE server = ?2")?;
+
+        for merge in &merges {
+            let target = resolve_merge_target(&targets, &merge.old_server)?;
+            let rows = select_stmt.query_map(params![merge.old_server], |row| {
+                Ok((
+                    row.get::<_, i32>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?;
+            for row in rows {
+                let (account_id, name, role_id) = row?;
+                let role = MigratedRole {
+                    role_id,
+                    old_server: merge.old_server.clone(),
+                    new_server: target.clone(),
+                };
+                match affected
+                    .iter_mut()
+                    .find(|account| account.account_id == account_id)
+                {
+                    Some(account) => account.roles.push(role),
+                    None => affected.push(AffectedAccount {
+                        account_id,
+                        name,
+                        roles: vec![role],
+                    }),
+                }
+            }
+            migrated_roles += update_stmt.execute(params![target, merge.old_server])?;
+        }
+    }
+
+    if !dry_run {
+        let now = unix_now();
+        for merge in &mut merges {
+            merge.applied_at = Some(now);
+        }
+        batch.execute(
+            "UPDATE jx3_server_merge SET applied_at = ?1
+             WHERE effective_date <= date('now', 'localtime')",
+            params![now],
+        )?;
+        batch.commit()?;
     }
+
+    Ok(MergeReport {
+        dry_run,
+        applied_merges: merges,
+        migrated_roles,
+        affected_accounts: affected,
+    })
+}
+
+/// 读取区服数据来源设置；未设置或内容无法解析时使用 JX3API。
+pub(crate) fn get_jx3_provider_config(db: &Database) -> AppResult<ProviderConfig> {
+    let Some(value) = get_setting(db, JX3_PROVIDER_KEY)? else {
+        return Ok(ProviderConfig::default());
+    };
+    Ok(serde_json::from_str(&value).unwrap_or_else(|e| {
+        println!("parse jx3 provider config error: {:?}", e);
+        ProviderConfig::default()
+    }))
+}
+
+pub(crate) fn save_jx3_provider_config(db: &Database, config: &ProviderConfig) -> AppResult<()> {
+    config.validate()?;
+    let mut conn = db.get()?;
+    let batch = conn.transaction()?;
+    set_setting_with_conn(&batch, JX3_PROVIDER_KEY, &serde_json::to_string(config)?)?;
+    // 更换来源后旧的校验信息不再适用
+    clear_jx3_http_cache_with_conn(&batch)?;
+    batch.commit()?;
     Ok(())
 }
 
-pub(crate) fn query_all_jx3_servers() -> Result<Vec<Jx3Server>> {
-    let conn = DB_CONNECTION.lock().unwrap();
-    let mut stmt = conn.prepare(
-        "SELECT id, zone, server, status, last_update_time FROM jx3_server ORDER BY zone ASC, server ASC",
-    )?;
-    let rows = stmt.query_map([], |row| {
-        Ok(Jx3Server {
-            id: row.get(0)?,
-            zone: row.get(1)?,
-            server: row.get(2)?,
-            status: row.get(3)?,
-            last_update_time: row.get(4)?,
-        })
-    })?;
+pub(crate) fn get_jx3_sync_interval(db: &Database) -> AppResult<u64> {
+    Ok(get_setting(db, JX3_SYNC_INTERVAL_KEY)?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_JX3_SYNC_INTERVAL_SECS))
+}
 
-    let mut servers = Vec::new();
-    for row in rows {
-        servers.push(row?);
+/// 保存后台刷新间隔（秒），0 表示关闭后台刷新。
+fn validate_jx3_sync_interval(secs: u64) -> AppResult<()> {
+    if secs != 0 && secs < MIN_JX3_SYNC_INTERVAL_SECS {
+        return Err(AppError::Validation(format!(
+            "刷新间隔不能小于 {MIN_JX3_SYNC_INTERVAL_SECS} 秒"
+        )));
     }
-    Ok(servers)
+    Ok(())
 }
 
-pub(crate) fn sync_jx3_servers(force_fallback: bool) -> Result<bool> {
-    let network_enabled = get_setting("network_sync_enabled")?
-        .map(|v| v == "1")
-        .unwrap_or(false);
+pub(crate) fn save_jx3_sync_interval(db: &Database, secs: u64) -> AppResult<()> {
+    validate_jx3_sync_interval(secs)?;
+    set_setting(db, JX3_SYNC_INTERVAL_KEY, &secs.to_string())
+}
 
-    let servers = if !force_fallback && network_enabled {
-        match fetch_servers_from_api() {
-            Ok(list) => list,
-            Err(err) => {
-                println!("JX3API 同步失败，使用兜底数据: {err}");
-                fallback_servers()
-            }
-        }
-    } else {
-        fallback_servers()
-    };
+pub(crate) fn get_jx3_maintenance_status(db: &Database) -> AppResult<String> {
+    Ok(get_setting(db, JX3_MAINTENANCE_STATUS_KEY)?
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_JX3_MAINTENANCE_STATUS.to_string()))
+}
+
+fn normalize_jx3_maintenance_status(status: &str) -> AppResult<String> {
+    let status = status.trim();
+    if status.is_empty() {
+        return Err(AppError::Validation("维护状态文字不能为空".to_string()));
+    }
+    Ok(status.to_string())
+}
 
-    let conn = DB_CONNECTION.lock().unwrap();
-    upsert_jx3_servers_with_conn(&conn, &servers)?;
-    set_setting_with_conn(&conn, "jx3_server_last_sync", &chrono_lite_now())?;
-    Ok(network_enabled && !force_fallback)
+pub(crate) fn save_jx3_maintenance_status(db: &Database, status: &str) -> AppResult<()> {
+    set_setting(
+        db,
+        JX3_MAINTENANCE_STATUS_KEY,
+        &normalize_jx3_maintenance_status(status)?,
+    )
 }
 
-fn chrono_lite_now() -> String {
+fn unix_now() -> i64 {
     use std::time::{SystemTime, UNIX_EPOCH};
-    let secs = SystemTime::now()
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs())
-        .unwrap_or(0);
-    secs.to_string()
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn chrono_lite_now() -> String {
+    unix_now().to_string()
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -1032,25 +3728,33 @@ pub(crate) struct NetworkSyncSettings {
     pub(crate) last_sync: Option<String>,
 }
 
-pub(crate) fn get_network_sync_settings() -> Result<NetworkSyncSettings> {
+pub(crate) fn get_network_sync_settings(db: &Database) -> AppResult<NetworkSyncSettings> {
     Ok(NetworkSyncSettings {
-        enabled: get_setting("network_sync_enabled")?
+        enabled: get_setting(db, "network_sync_enabled")?
             .map(|v| v == "1")
             .unwrap_or(false),
-        prompted: get_setting("network_sync_prompted")?
+        prompted: get_setting(db, "network_sync_prompted")?
             .map(|v| v == "1")
             .unwrap_or(false),
-        last_sync: get_setting("jx3_server_last_sync")?,
+        last_sync: get_setting(db, "jx3_server_last_sync")?,
     })
 }
 
-pub(crate) fn save_network_sync_settings(enabled: bool, prompted: bool) -> Result<()> {
-    set_setting("network_sync_enabled", if enabled { "1" } else { "0" })?;
-    set_setting("network_sync_prompted", if prompted { "1" } else { "0" })?;
+pub(crate) fn save_network_sync_settings(
+    db: &Database,
+    enabled: bool,
+    prompted: bool,
+) -> AppResult<()> {
+    set_setting(db, "network_sync_enabled", if enabled { "1" } else { "0" })?;
+    set_setting(
+        db,
+        "network_sync_prompted",
+        if prompted { "1" } else { "0" },
+    )?;
     Ok(())
 }
 
-pub(crate) fn get_export_fields() -> Result<Vec<String>> {
+pub(crate) fn get_export_fields(db: &Database) -> AppResult<Vec<String>> {
     let default = vec![
         "name".to_string(),
         "username".to_string(),
@@ -1058,7 +3762,7 @@ pub(crate) fn get_export_fields() -> Result<Vec<String>> {
         "roles".to_string(),
         "description".to_string(),
     ];
-    match get_setting("export_fields")? {
+    match get_setting(db, "export_fields")? {
         Some(value) => {
             let parsed: Vec<String> = serde_json::from_str(&value).unwrap_or(default.clone());
             if parsed.is_empty() {
@@ -1071,18 +3775,555 @@ pub(crate) fn get_export_fields() -> Result<Vec<String>> {
     }
 }
 
-pub(crate) fn save_export_fields(fields: &[String]) -> Result<()> {
-    let value = serde_json::to_string(fields).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
-    set_setting("export_fields", &value)
+pub(crate) fn save_export_fields(db: &Database, fields: &[String]) -> AppResult<()> {
+    let value = serde_json::to_string(fields)?;
+    set_setting(db, "export_fields", &value)
 }
 
-pub(crate) fn get_favorite_filter() -> Result<i32> {
-    Ok(get_setting("favorite_filter")?
+pub(crate) fn get_vault_idle_timeout(db: &Database) -> AppResult<u64> {
+    Ok(get_setting(db, VAULT_IDLE_TIMEOUT_KEY)?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(vault::DEFAULT_IDLE_TIMEOUT_SECS))
+}
+
+pub(crate) fn save_vault_idle_timeout(db: &Database, secs: u64) -> AppResult<()> {
+    set_setting(db, VAULT_IDLE_TIMEOUT_KEY, &secs.to_string())?;
+    vault::set_idle_timeout(secs);
+    Ok(())
+}
+
+pub(crate) fn get_clipboard_clear_seconds(db: &Database) -> AppResult<u64> {
+    Ok(get_setting(db, CLIPBOARD_CLEAR_SECONDS_KEY)?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_CLIPBOARD_CLEAR_SECONDS))
+}
+
+pub(crate) fn save_clipboard_clear_seconds(db: &Database, secs: u64) -> AppResult<()> {
+    set_setting(db, CLIPBOARD_CLEAR_SECONDS_KEY, &secs.to_string())
+}
+
+pub(crate) fn get_snapshot_retention_with_conn(conn: &Connection) -> Result<u32> {
+    Ok(get_setting_with_conn(conn, SNAPSHOT_RETENTION_KEY)?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_SNAPSHOT_RETENTION))
+}
+
+pub(crate) fn get_snapshot_retention(db: &Database) -> AppResult<u32> {
+    Ok(get_snapshot_retention_with_conn(&*db.get()?)?)
+}
+
+fn validate_snapshot_retention(count: u32) -> AppResult<()> {
+    if count > MAX_SNAPSHOT_RETENTION {
+        return Err(AppError::Validation(format!(
+            "快照保留份数不能超过 {MAX_SNAPSHOT_RETENTION}"
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn save_snapshot_retention(db: &Database, count: u32) -> AppResult<()> {
+    validate_snapshot_retention(count)?;
+    set_setting(db, SNAPSHOT_RETENTION_KEY, &count.to_string())
+}
+
+pub(crate) fn get_favorite_filter(db: &Database) -> AppResult<i32> {
+    Ok(get_setting(db, "favorite_filter")?
         .and_then(|v| v.parse().ok())
         .unwrap_or(0))
 }
 
-pub(crate) fn save_favorite_filter(value: i32) -> Result<()> {
-    set_setting("favorite_filter", &value.to_string())
+pub(crate) fn save_favorite_filter(db: &Database, value: i32) -> AppResult<()> {
+    set_setting(db, "favorite_filter", &value.to_string())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::{Mutex, MutexGuard};
+
+    const MASTER_PASSWORD: &str = "correct horse";
+
+    /// 账号库密钥是进程级全局状态，涉及解锁的测试需要串行执行。
+    static VAULT_LOCK: Mutex<()> = Mutex::new(());
+
+    fn lock_vault() -> MutexGuard<'static, ()> {
+        VAULT_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 位于临时目录的测试数据库，离开作用域时锁定账号库并删除整个目录（含快照）。
+    struct TempDb {
+        dir: PathBuf,
+        db: Database,
+    }
+
+    impl TempDb {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("auto-paste-test-{}-{name}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            let db = initialize_database(&dir.join("data.db")).unwrap();
+            TempDb { dir, db }
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            vault::lock();
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn account(name: &str, username: &str, password: &str) -> Account {
+        Account {
+            id: None,
+            name: name.to_string(),
+            username: username.to_string(),
+            password: password.to_string(),
+            sequence: None,
+            liked: None,
+            description: None,
+            last_update_time: None,
+            account_category_ids: None,
+            roles: None,
+        }
+    }
+
+    fn category(name: &str) -> Category {
+        Category {
+            id: None,
+            name: name.to_string(),
+            sequence: None,
+            last_update_time: None,
+            account_ids: None,
+        }
+    }
+
+    fn account_id(db: &Database, name: &str) -> i32 {
+        db.get()
+            .unwrap()
+            .query_row(
+                "SELECT id FROM account WHERE name = ?",
+                params![name],
+                |row| row.get(0),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn account_credentials_round_trip_and_stay_bound_to_their_row() {
+        let _guard = lock_vault();
+        let temp = TempDb::new("round-trip");
+        setup_vault(&temp.db, MASTER_PASSWORD).unwrap();
+        insert_account(&temp.db, &account("甲", "alice", "p@ss w0rd ")).unwrap();
+        insert_account(&temp.db, &account("乙", "bob", "other")).unwrap();
+        let alice = account_id(&temp.db, "甲");
+        let bob = account_id(&temp.db, "乙");
+
+        assert_eq!(
+            get_account_field(&temp.db, alice, AccountField::Username).unwrap(),
+            "alice"
+        );
+        assert_eq!(
+            get_account_field(&temp.db, alice, AccountField::Password).unwrap(),
+            "p@ss w0rd "
+        );
+
+        // 把甲的密码密文复制给乙后无法解密
+        let conn = temp.db.get().unwrap();
+        conn.execute(
+            "UPDATE account SET password = (SELECT password FROM account WHERE id = ?1) WHERE id = ?2",
+            params![alice, bob],
+        )
+        .unwrap();
+        drop(conn);
+        assert!(get_account_field(&temp.db, bob, AccountField::Password).is_err());
+    }
+
+    #[test]
+    fn unlock_rejects_wrong_password() {
+        let _guard = lock_vault();
+        let temp = TempDb::new("wrong-password");
+        setup_vault(&temp.db, MASTER_PASSWORD).unwrap();
+        insert_account(&temp.db, &account("甲", "alice", "secret")).unwrap();
+        vault::lock();
+
+        assert!(unlock_vault(&temp.db, "wrong horse").is_err());
+        assert!(!vault::is_unlocked());
+        assert!(
+            get_account_field(&temp.db, account_id(&temp.db, "甲"), AccountField::Password)
+                .is_err()
+        );
+
+        unlock_vault(&temp.db, MASTER_PASSWORD).unwrap();
+        assert_eq!(
+            get_account_field(&temp.db, account_id(&temp.db, "甲"), AccountField::Password)
+                .unwrap(),
+            "secret"
+        );
+    }
+
+    #[test]
+    fn migration_encrypts_plaintext_accounts_in_place() {
+        let _guard = lock_vault();
+        let temp = TempDb::new("plaintext-migration");
+        {
+            // 模拟 1.1.4 的数据库：明文账号、尚未登记待加密
+            let conn = temp.db.get().unwrap();
+            conn.execute(
+                "INSERT INTO account (name, username, password) VALUES ('旧账号', 'legacy-user', 'legacy-pass')",
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                "DELETE FROM app_setting WHERE key = ?",
+                params![VAULT_PENDING_ENCRYPTION_KEY],
+            )
+            .unwrap();
+            set_setting_with_conn(&conn, APP_VERSION_KEY, "1.1.4").unwrap();
+        }
+        reload_database(&temp.db).unwrap();
+        assert!(get_vault_status(&temp.db).unwrap().pending_encryption);
+
+        setup_vault(&temp.db, MASTER_PASSWORD).unwrap();
+        let (id, username, password): (i32, String, String) = temp
+            .db
+            .get()
+            .unwrap()
+            .query_row("SELECT id, username, password FROM account", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+        assert!(vault::is_encrypted(&username) && vault::is_encrypted(&password));
+        assert!(!get_vault_status(&temp.db).unwrap().pending_encryption);
+        assert_eq!(
+            get_account_field(&temp.db, id, AccountField::Username).unwrap(),
+            "legacy-user"
+        );
+        assert_eq!(
+            get_account_field(&temp.db, id, AccountField::Password).unwrap(),
+            "legacy-pass"
+        );
+    }
+
+    fn count(db: &Database, table: &str) -> i64 {
+        db.get()
+            .unwrap()
+            .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn pooled_reads_are_not_blocked_by_an_open_write() {
+        let temp = TempDb::new("pool");
+        let mut writer = temp.db.get().unwrap();
+        let batch = writer.transaction().unwrap();
+        batch
+            .execute(
+                "INSERT INTO category (name, sequence) VALUES ('未提交', 1)",
+                [],
+            )
+            .unwrap();
+
+        // WAL 模式下另一个连接可以读取，且看不到未提交的数据
+        assert_eq!(count(&temp.db, "category"), 0);
+        batch.commit().unwrap();
+        drop(writer);
+        assert_eq!(count(&temp.db, "category"), 1);
+    }
+
+    #[test]
+    fn password_import_duplicates_match_name_and_username() {
+        let _guard = lock_vault();
+        let temp = TempDb::new("duplicate-key");
+        setup_vault(&temp.db, MASTER_PASSWORD).unwrap();
+        update_account(&temp.db, &account("github.com", "alice", "old")).unwrap();
+        let row = |line: usize, name: &str| CsvAccountRow {
+            line,
+            name: name.to_string(),
+            username: Some("alice".to_string()),
+            password: Some("new".to_string()),
+            description: None,
+            categories: Vec::new(),
+            roles: Vec::new(),
+        };
+        let rows = vec![row(1, "github.com"), row(2, "gitlab.com")];
+
+        let mut report = CsvImportReport::default();
+        import_csv_accounts(
+            &temp.db,
+            &rows,
+            CsvDuplicateKey::NameAndUsername,
+            CsvDuplicateStrategy::Skip,
+            &mut report,
+        )
+        .unwrap();
+        assert_eq!((report.created, report.skipped), (1, 1));
+        assert_eq!(count(&temp.db, "account"), 2);
+    }
+
+    #[test]
+    fn copy_database_file_keeps_existing_data() {
+        let _guard = lock_vault();
+        let temp = TempDb::new("copy-file");
+        setup_vault(&temp.db, MASTER_PASSWORD).unwrap();
+        update_account(&temp.db, &account("甲", "alice", "secret")).unwrap();
+        let source = temp.dir.join("source.db");
+        relocate_database(&temp.db, &source).unwrap();
+        let target = temp.dir.join("moved").join("target.db");
+
+        copy_database_file(&source, &target).unwrap();
+        let copied = initialize_database(&target).unwrap();
+        assert_eq!(count(&copied, "account"), 1);
+
+        // 目标已存在时保留原文件
+        unlock_vault(&temp.db, MASTER_PASSWORD).unwrap();
+        update_account(&temp.db, &account("乙", "bob", "secret")).unwrap();
+        copy_database_file(&source, &target).unwrap();
+        assert_eq!(count(&copied, "account"), 1);
+    }
+
+    #[test]
+    fn file_snapshot_ignores_retention() {
+        let temp = TempDb::new("file-snapshot");
+        save_snapshot_retention(&temp.db, 0).unwrap();
+
+        let taken = snapshot::take_file_snapshot(&temp.db.path(), SnapshotReason::Delete).unwrap();
+        assert_eq!(
+            taken.map(|snapshot| snapshot.reason),
+            Some(SnapshotReason::Delete)
+        );
+        assert_eq!(snapshot::list_snapshots(&temp.db).unwrap().len(), 1);
+        assert!(
+            snapshot::take_file_snapshot(&temp.dir.join("missing.db"), SnapshotReason::Delete)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn categories_require_unlocked_vault() {
+        let _guard = lock_vault();
+        let temp = TempDb::new("category-lock");
+        setup_vault(&temp.db, MASTER_PASSWORD).unwrap();
+        create_category(&temp.db, &category("工作")).unwrap();
+        vault::lock();
+
+        assert!(matches!(
+            query_all_categories(&temp.db),
+            Err(AppError::Locked(_))
+        ));
+        assert!(matches!(
+            create_category(&temp.db, &category("游戏")),
+            Err(AppError::Locked(_))
+        ));
+        assert!(matches!(
+            reorder_categories(&temp.db, &[1]),
+            Err(AppError::Locked(_))
+        ));
+        assert_eq!(count(&temp.db, "category"), 1);
+    }
+
+    #[test]
+    fn status_history_ignores_fallback_overwrites() {
+        let temp = TempDb::new("status-history");
+        let conn = temp.db.get().unwrap();
+        let write = |status: &str, observed: bool| {
+            let server = Jx3Server {
+                id: None,
+                zone: "测试区".to_string(),
+                server: "测试服".to_string(),
+                status: status.to_string(),
+                last_update_time: None,
+                watched: false,
+            };
+            upsert_jx3_servers_with_conn(&conn, &[server], observed).unwrap();
+        };
+        let history = || -> Vec<String> {
+            conn.prepare(
+                "SELECT status FROM jx3_server_status_history WHERE server = '测试服' ORDER BY id",
+            )
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap()
+        };
+
+        write("正常", true);
+        write("爆满", false);
+        write("正常", true);
+        assert_eq!(history(), ["正常"]);
+        write("维护", true);
+        assert_eq!(history(), ["正常", "维护"]);
+    }
+
+    #[test]
+    fn vault_import_remaps_ids() {
+        let _guard = lock_vault();
+        let source = TempDb::new("import-source");
+        setup_vault(&source.db, MASTER_PASSWORD).unwrap();
+        create_category(&source.db, &category("工作")).unwrap();
+        let work = source
+            .db
+            .get()
+            .unwrap()
+            .query_row("SELECT id FROM category", [], |row| row.get(0))
+            .unwrap();
+        insert_account(
+            &source.db,
+            &Account {
+                account_category_ids: Some(vec![work]),
+                ..account("甲", "alice", "secret")
+            },
+        )
+        .unwrap();
+        let document = export_vault(&source.db).unwrap();
+        vault::lock();
+
+        // 目标库已有数据，导入的账号与分类会拿到不同的 id
+        let target = TempDb::new("import-target");
+        setup_vault(&target.db, MASTER_PASSWORD).unwrap();
+        create_category(&target.db, &category("其他")).unwrap();
+        insert_account(&target.db, &account("占位", "placeholder", "x")).unwrap();
+        insert_account(&target.db, &account("占位二", "placeholder2", "y")).unwrap();
+        import_vault(&target.db, &document).unwrap();
+
+        let conn = target.db.get().unwrap();
+        let imported: i32 = conn
+            .query_row("SELECT id FROM account WHERE name = '甲'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        let linked: String = conn
+            .query_row(
+                "SELECT c.name FROM account_category ac JOIN category c ON c.id = ac.category_id WHERE ac.account_id = ?",
+                params![imported],
+                |row| row.get(0),
+            )
+            .unwrap();
+        drop(conn);
+        assert_ne!(Some(imported), document.accounts[0].id);
+        assert_eq!(linked, "工作");
+        assert_eq!(
+            get_account_field(&target.db, imported, AccountField::Password).unwrap(),
+            "secret"
+        );
+    }
+
+    #[test]
+    fn vault_import_validates_settings() {
+        let _guard = lock_vault();
+        let temp = TempDb::new("import-settings");
+        setup_vault(&temp.db, MASTER_PASSWORD).unwrap();
+        set_setting(
+            &temp.db,
+            JX3_HTTP_CACHE_KEY,
+            r#"{"url":"https://example.com"}"#,
+        )
+        .unwrap();
+
+        let mut document = export_vault(&temp.db).unwrap();
+        document.settings = [
+            (JX3_SYNC_INTERVAL_KEY, "5"),
+            (SNAPSHOT_RETENTION_KEY, "1000"),
+            ("vault_salt", "AAAA"),
+            (JX3_MAINTENANCE_STATUS_KEY, " 停服 "),
+            (
+                JX3_PROVIDER_KEY,
+                r#"{"kind":"local_file","path":"servers.json"}"#,
+            ),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+        let summary = import_vault(&temp.db, &document).unwrap();
+
+        assert_eq!((summary.settings, summary.skipped), (2, 3));
+        assert_eq!(
+            get_jx3_sync_interval(&temp.db).unwrap(),
+            DEFAULT_JX3_SYNC_INTERVAL_SECS
+        );
+        assert_eq!(
+            get_snapshot_retention(&temp.db).unwrap(),
+            DEFAULT_SNAPSHOT_RETENTION
+        );
+        assert_eq!(get_jx3_maintenance_status(&temp.db).unwrap(), "停服");
+        assert!(matches!(
+            get_jx3_provider_config(&temp.db).unwrap(),
+            ProviderConfig::LocalFile { .. }
+        ));
+        assert!(get_setting(&temp.db, JX3_HTTP_CACHE_KEY).unwrap().is_none());
+    }
+
+    #[test]
+    fn restore_keeps_undo_snapshot_when_retention_is_zero() {
+        let _guard = lock_vault();
+        let temp = TempDb::new("restore-undo");
+        let daily =
+            snapshot::take_snapshot_with_conn(&temp.db.get().unwrap(), SnapshotReason::Daily)
+                .unwrap()
+                .unwrap();
+        save_snapshot_retention(&temp.db, 0).unwrap();
+
+        snapshot::restore_snapshot(&temp.db, &daily.name).unwrap();
+        let reasons: Vec<_> = snapshot::list_snapshots(&temp.db)
+            .unwrap()
+            .into_iter()
+            .map(|snapshot| snapshot.reason)
+            .collect();
+        assert!(reasons.contains(&SnapshotReason::Restore));
+        assert!(reasons.contains(&SnapshotReason::Daily));
+    }
+
+    #[test]
+    fn csv_dry_run_rolls_back() {
+        let _guard = lock_vault();
+        let temp = TempDb::new("dry-run");
+        setup_vault(&temp.db, MASTER_PASSWORD).unwrap();
+        let rows = vec![CsvAccountRow {
+            line: 2,
+            name: "甲".to_string(),
+            username: Some("alice".to_string()),
+            password: Some("secret".to_string()),
+            description: None,
+            categories: vec!["新分类".to_string()],
+            roles: Vec::new(),
+        }];
+
+        let mut report = CsvImportReport {
+            dry_run: true,
+            ..CsvImportReport::default()
+        };
+        import_csv_accounts(
+            &temp.db,
+            &rows,
+            CsvDuplicateKey::Username,
+            CsvDuplicateStrategy::Skip,
+            &mut report,
+        )
+        .unwrap();
+        assert_eq!((report.created, report.categories_created), (1, 1));
+        assert_eq!(
+            (count(&temp.db, "account"), count(&temp.db, "category")),
+            (0, 0)
+        );
+
+        let mut report = CsvImportReport::default();
+        import_csv_accounts(
+            &temp.db,
+            &rows,
+            CsvDuplicateKey::Username,
+            CsvDuplicateStrategy::Skip,
+            &mut report,
+        )
+        .unwrap();
+        assert_eq!(
+            (count(&temp.db, "account"), count(&temp.db, "category")),
+            (1, 1)
+        );
+    }
+}
@@ -0,0 +1,322 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub(crate) const DB_FILE_NAME: &str = "auto_paste.db";
+/// 指定数据库文件路径的环境变量，优先级高于设置。
+pub(crate) const DB_PATH_ENV: &str = "AUTO_PASTE_DB_PATH";
+const STORAGE_CONFIG_FILE: &str = "storage.json";
+const PROFILE_DIR: &str = "vaults";
+
+pub(crate) const DEFAULT_PROFILE_ID: &str = "default";
+const DEFAULT_PROFILE_NAME: &str = "默认账号库";
+
+/// 数据库位置无法存放在数据库自身中，单独保存在应用数据目录下的配置文件里。
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StorageConfig {
+    database_path: Option<String>,
+    #[serde(default)]
+    active_profile: Option<String>,
+    #[serde(default)]
+    profiles: Vec<VaultProfile>,
+}
+
+/// 账号库档案：每个档案对应一个独立的 SQLite 文件。
+///
+/// 默认档案沿用 `resolve_database_location` 解析出的位置，其余档案存放在应用数据目录的 `vaults` 子目录下。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct VaultProfile {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) file: Option<String>,
+}
+
+fn invalid_input(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.into())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DatabasePathSource {
+    Env,
+    Setting,
+    Default,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct DatabaseLocation {
+    pub(crate) path: PathBuf,
+    pub(crate) source: DatabasePathSource,
+}
+
+fn read_config(data_dir: &Path) -> StorageConfig {
+    fs::read_to_string(data_dir.join(STORAGE_CONFIG_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_config(data_dir: &Path, config: &StorageConfig) -> io::Result<()> {
+    fs::create_dir_all(data_dir)?;
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(data_dir.join(STORAGE_CONFIG_FILE), content)
+}
+
+/// 按「环境变量 → 设置 → 应用数据目录」的顺序确定数据库文件位置。
+pub(crate) fn resolve_database_location(data_dir: &Path) -> DatabaseLocation {
+    if let Some(path) = std::env::var_os(DB_PATH_ENV).filter(|value| !value.is_empty()) {
+        return DatabaseLocation {
+            path: PathBuf::from(path),
+            source: DatabasePathSource::Env,
+        };
+    }
+
+    if let Some(path) = read_config(data_dir)
+        .database_path
+        .filter(|value| !value.trim().is_empty())
+    {
+        return DatabaseLocation {
+            path: PathBuf::from(path),
+            source: DatabasePathSource::Setting,
+        };
+    }
+
+    DatabaseLocation {
+        path: data_dir.join(DB_FILE_NAME),
+        source: DatabasePathSource::Default,
+    }
+}
+
+/// 保存自定义数据库路径，传入 `None` 恢复为默认位置。
+pub(crate) fn save_database_path_override(data_dir: &Path, path: Option<&Path>) -> io::Result<()> {
+    let mut config = read_config(data_dir);
+    config.database_path = path.map(|value| value.to_string_lossy().into_owned());
+    write_config(data_dir, &config)
+}
+
+pub(crate) fn ensure_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent),
+        _ => Ok(()),
+    }
+}
+
+/// 旧版本把数据库建在当前工作目录下；目标位置尚无数据库时将其迁移过去，返回是否发生了迁移。
+pub(crate) fn migrate_legacy_database(target: &Path) -> io::Result<bool> {
+    let legacy = match std::env::current_dir() {
+        Ok(dir) => dir.join(DB_FILE_NAME),
+        Err(_) => return Ok(false),
+    };
+
+    // 目标已存在（包括与旧位置相同）时不做任何处理
+    if target.exists() || !legacy.is_file() {
+        return Ok(false);
+    }
+
+    ensure_parent_dir(target)?;
+    // 跨磁盘时 rename 会失败，退回到复制后删除
+    if fs::rename(&legacy, target).is_err() {
+        fs::copy(&legacy, target)?;
+        fs::remove_file(&legacy)?;
+    }
+    println!(
+        "已将旧数据库 {} 迁移至 {}",
+        legacy.display(),
+        target.display()
+    );
+
+    Ok(true)
+}
+
+fn profiles_with_default(config: &StorageConfig) -> Vec<VaultProfile> {
+    let mut profiles = config.profiles.clone();
+    if !profiles
+        .iter()
+        .any(|profile| profile.id == DEFAULT_PROFILE_ID)
+    {
+        profiles.insert(
+            0,
+            VaultProfile {
+                id: DEFAULT_PROFILE_ID.to_string(),
+                name: DEFAULT_PROFILE_NAME.to_string(),
+                file: None,
+            },
+        );
+    }
+    profiles
+}
+
+fn active_profile_id(config: &StorageConfig, profiles: &[VaultProfile]) -> String {
+    config
+        .active_profile
+        .as_ref()
+        .filter(|id| profiles.iter().any(|profile| &profile.id == *id))
+        .cloned()
+        .unwrap_or_else(|| DEFAULT_PROFILE_ID.to_string())
+}
+
+fn validate_profile_name(
+    profiles: &[VaultProfile],
+    name: &str,
+    except_id: Option<&str>,
+) -> io::Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(invalid_input("账号库名称不能为空"));
+    }
+    if profiles
+        .iter()
+        .any(|profile| profile.name == name && Some(profile.id.as_str()) != except_id)
+    {
+        return Err(invalid_input(format!("账号库「{name}」已存在")));
+    }
+    Ok(name.to_string())
+}
+
+/// 返回当前激活的档案 ID 及全部档案（默认档案始终排在第一位）。
+pub(crate) fn list_profiles(data_dir: &Path) -> (String, Vec<VaultProfile>) {
+    let config = read_config(data_dir);
+    let profiles = profiles_with_default(&config);
+    let active = active_profile_id(&config, &profiles);
+    (active, profiles)
+}
+
+pub(crate) fn profile_path(data_dir: &Path, profile: &VaultProfile) -> PathBuf {
+    match &profile.file {
+        Some(file) => data_dir.join(PROFILE_DIR).join(file),
+        None => resolve_database_location(data_dir).path,
+    }
+}
+
+pub(crate) fn find_profile(data_dir: &Path, id: &str) -> io::Result<VaultProfile> {
+    list_profiles(data_dir)
+        .1
+        .into_iter()
+        .find(|profile| profile.id == id)
+        .ok_or_else(|| invalid_input(format!("账号库不存在: {id}")))
+}
+
+/// 当前激活档案对应的数据库文件位置。
+pub(crate) fn resolve_active_database(data_dir: &Path) -> PathBuf {
+    let (active, profiles) = list_profiles(data_dir);
+    match profiles.iter().find(|profile| profile.id == active) {
+        Some(profile) => profile_path(data_dir, profile),
+        None => resolve_database_location(data_dir).path,
+    }
+}
+
+pub(crate) fn create_profile(data_dir: &Path, name: &str) -> io::Result<VaultProfile> {
+    let mut config = read_config(data_dir);
+    let mut profiles = profiles_with_default(&config);
+    let name = validate_profile_name(&profiles, name, None)?;
+
+    let mut millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    // 同一毫秒内创建或残留同名文件时顺延，保证每个档案使用独立的文件
+    let id = loop {
+        let id = format!("vault-{millis}");
+        let taken = profiles.iter().any(|profile| profile.id == id)
+            || data_dir.join(PROFILE_DIR).join(format!("{id}.db")).exists();
+        if !taken {
+            break id;
+        }
+        millis += 1;
+    };
+    let profile = VaultProfile {
+        file: Some(format!("{id}.db")),
+        id,
+        name,
+    };
+
+    profiles.push(profile.clone());
+    config.profiles = profiles;
+    write_config(data_dir, &config)?;
+    Ok(profile)
+}
+
+pub(crate) fn rename_profile(data_dir: &Path, id: &str, name: &str) -> io::Result<()> {
+    let mut config = read_config(data_dir);
+    let mut profiles = profiles_with_default(&config);
+    let name = validate_profile_name(&profiles, name, Some(id))?;
+
+    let profile = profiles
+        .iter_mut()
+        .find(|profile| profile.id == id)
+        .ok_or_else(|| invalid_input(format!("账号库不存在: {id}")))?;
+    profile.name = name;
+
+    config.profiles = profiles;
+    write_config(data_dir, &config)
+}
+
+/// 返回可以删除的档案；默认档案与当前激活的档案不可删除。
+pub(crate) fn find_deletable_profile(data_dir: &Path, id: &str) -> io::Result<VaultProfile> {
+    let config = read_config(data_dir);
+    let profiles = profiles_with_default(&config);
+    if id == DEFAULT_PROFILE_ID {
+        return Err(invalid_input("默认账号库不可删除"));
+    }
+    if active_profile_id(&config, &profiles) == id {
+        return Err(invalid_input("不能删除正在使用的账号库"));
+    }
+    profiles
+        .into_iter()
+        .find(|profile| profile.id == id)
+        .ok_or_else(|| invalid_input(format!("账号库不存在: {id}")))
+}
+
+/// 从档案列表中移除并删除其数据库文件，可删除的档案见 `find_deletable_profile`。
+pub(crate) fn delete_profile(data_dir: &Path, id: &str) -> io::Result<()> {
+    let profile = find_deletable_profile(data_dir, id)?;
+    let mut config = read_config(data_dir);
+    let mut profiles = profiles_with_default(&config);
+    profiles.retain(|other| other.id != profile.id);
+
+    config.profiles = profiles;
+    write_config(data_dir, &config)?;
+
+    let path = profile_path(data_dir, &profile);
+    for suffix in ["", "-wal", "-shm", "-journal"] {
+        let mut file = path.clone().into_os_string();
+        file.push(suffix);
+        match fs::remove_file(PathBuf::from(file)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn set_active_profile(data_dir: &Path, id: &str) -> io::Result<()> {
+    let mut config = read_config(data_dir);
+    let profiles = profiles_with_default(&config);
+    if !profiles.iter().any(|profile| profile.id == id) {
+        return Err(invalid_input(format!("账号库不存在: {id}")));
+    }
+    config.active_profile = Some(id.to_string());
+    write_config(data_dir, &config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_created_together_get_distinct_files() {
+        let data_dir =
+            std::env::temp_dir().join(format!("auto-paste-test-{}-profiles", std::process::id()));
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let first = create_profile(&data_dir, "甲").unwrap();
+        let second = create_profile(&data_dir, "乙").unwrap();
+        assert_ne!(first.id, second.id);
+        assert_ne!(first.file, second.file);
+        assert_eq!(list_profiles(&data_dir).1.len(), 3);
+
+        let _ = fs::remove_dir_all(&data_dir);
+    }
+}
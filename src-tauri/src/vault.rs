@@ -0,0 +1,432 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+/// 加密字段前缀，用于区分已加密值与旧版明文值。
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const MIN_PASSWORD_LEN: usize = 8;
+/// 读取密钥派生参数时允许的上限为默认值的倍数，防止构造的备份文件耗尽内存或 CPU。
+const MAX_KDF_FACTOR: u32 = 4;
+
+const CHECK_PLAINTEXT: &str = "auto-paste-vault";
+const CHECK_AAD: &[u8] = b"app_setting.vault_check";
+
+/// 默认闲置 5 分钟后自动锁定，0 表示不自动锁定。
+pub(crate) const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 300;
+
+pub(crate) type VaultKey = Zeroizing<[u8; KEY_LEN]>;
+
+/// 锁定状态机：`key` 为 `None` 即已锁定；解锁后每次访问账号数据都会刷新 `last_activity`。
+struct VaultState {
+    key: Option<VaultKey>,
+    last_activity: Instant,
+    idle_timeout: Duration,
+}
+
+lazy_static::lazy_static! {
+    static ref VAULT_STATE: Mutex<VaultState> = Mutex::new(VaultState {
+        key: None,
+        last_activity: Instant::now(),
+        idle_timeout: Duration::from_secs(DEFAULT_IDLE_TIMEOUT_SECS),
+    });
+}
+
+#[derive(Debug)]
+pub(crate) enum VaultError {
+    Locked,
+    NotInitialized,
+    AlreadyInitialized,
+    WrongPassword,
+    WeakPassword,
+    WeakPassphrase,
+    WrongPassphrase,
+    Crypto(String),
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VaultError::Locked => write!(f, "账号库已锁定"),
+            VaultError::NotInitialized => write!(f, "尚未设置主密码"),
+            VaultError::AlreadyInitialized => write!(f, "主密码已设置"),
+            VaultError::WrongPassword => write!(f, "主密码错误"),
+            VaultError::WeakPassword => {
+                write!(f, "主密码长度至少为 {MIN_PASSWORD_LEN} 位")
+            }
+            VaultError::WeakPassphrase => {
+                write!(f, "备份口令长度至少为 {MIN_PASSWORD_LEN} 位")
+            }
+            VaultError::WrongPassphrase => write!(f, "备份口令错误或备份文件已损坏"),
+            VaultError::Crypto(message) => write!(f, "加解密失败: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for VaultError {}
+
+/// Argon2id 参数，随账号库一同持久化，便于日后调整强度时兼容旧库。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct KdfParams {
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        // OWASP 推荐的 Argon2id 最低配置：19 MiB 内存、2 次迭代、单线程
+        KdfParams {
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    fn encode(&self) -> String {
+        format!(
+            "argon2id:m={},t={},p={}",
+            self.m_cost, self.t_cost, self.p_cost
+        )
+    }
+
+    /// 解析 `encode` 的结果；格式错误或任一参数超过默认值的 `MAX_KDF_FACTOR` 倍时返回 `None`。
+    fn decode(value: &str) -> Option<Self> {
+        let params = value.strip_prefix("argon2id:")?;
+        let mut m_cost = None;
+        let mut t_cost = None;
+        let mut p_cost = None;
+        for part in params.split(',') {
+            let (name, number) = part.split_once('=')?;
+            let number: u32 = number.parse().ok()?;
+            match name {
+                "m" => m_cost = Some(number),
+                "t" => t_cost = Some(number),
+                "p" => p_cost = Some(number),
+                _ => return None,
+            }
+        }
+        let params = KdfParams {
+            m_cost: m_cost?,
+            t_cost: t_cost?,
+            p_cost: p_cost?,
+        };
+        let max = KdfParams::default();
+        let within_limits = params.m_cost <= max.m_cost * MAX_KDF_FACTOR
+            && params.t_cost <= max.t_cost * MAX_KDF_FACTOR
+            && params.p_cost <= max.p_cost * MAX_KDF_FACTOR;
+        within_limits.then_some(params)
+    }
+}
+
+/// 持久化在 `app_setting` 中的账号库元数据。
+#[derive(Debug, Clone)]
+pub(crate) struct VaultMeta {
+    pub(crate) salt: String,
+    pub(crate) kdf: String,
+    pub(crate) check: String,
+}
+
+fn derive_key(password: &str, salt: &[u8], params: &KdfParams) -> Result<VaultKey, VaultError> {
+    let argon_params = Params::new(params.m_cost, params.t_cost, params.p_cost, Some(KEY_LEN))
+        .map_err(|e| VaultError::Crypto(e.to_string()))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params);
+
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    argon2
+        .hash_password_into(password.as_bytes(), salt, key.as_mut())
+        .map_err(|e| VaultError::Crypto(e.to_string()))?;
+    Ok(key)
+}
+
+pub(crate) fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// 账号用户名的附加认证数据，绑定账号 id，密文挪到其他账号上无法解密。
+pub(crate) fn username_aad(account_id: i32) -> Vec<u8> {
+    format!("account.username:{account_id}").into_bytes()
+}
+
+/// 账号密码的附加认证数据，绑定账号 id。
+pub(crate) fn password_aad(account_id: i32) -> Vec<u8> {
+    format!("account.password:{account_id}").into_bytes()
+}
+
+/// 使用 XChaCha20-Poly1305 加密，输出 `enc:v1:` + base64(nonce || ciphertext)。
+pub(crate) fn encrypt_with(
+    key: &VaultKey,
+    plaintext: &str,
+    aad: &[u8],
+) -> Result<String, VaultError> {
+    let cipher = XChaCha20Poly1305::new(key.as_ref().into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext.as_bytes(),
+                aad,
+            },
+        )
+        .map_err(|e| VaultError::Crypto(e.to_string()))?;
+
+    let mut payload = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+    Ok(format!("{ENCRYPTED_PREFIX}{}", STANDARD.encode(payload)))
+}
+
+/// 解密字段；没有加密前缀的值视为错误，旧版明文只能经由解锁时的迁移加密。
+pub(crate) fn decrypt_with(key: &VaultKey, stored: &str, aad: &[u8]) -> Result<String, VaultError> {
+    let Some(encoded) = stored.strip_prefix(ENCRYPTED_PREFIX) else {
+        return Err(VaultError::Crypto("字段未加密".to_string()));
+    };
+
+    let payload = STANDARD
+        .decode(encoded)
+        .map_err(|e| VaultError::Crypto(e.to_string()))?;
+    if payload.len() < NONCE_LEN {
+        return Err(VaultError::Crypto("密文长度不足".to_string()));
+    }
+
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    let cipher = XChaCha20Poly1305::new(key.as_ref().into());
+    let plaintext = cipher
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| VaultError::Crypto("密文校验失败".to_string()))?;
+
+    String::from_utf8(plaintext).map_err(|e| VaultError::Crypto(e.to_string()))
+}
+
+/// 首次设置主密码：生成盐与校验值，返回待持久化的元数据及派生出的密钥。
+pub(crate) fn create(password: &str) -> Result<(VaultMeta, VaultKey), VaultError> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(VaultError::WeakPassword);
+    }
+
+    let params = KdfParams::default();
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let key = derive_key(password, &salt, &params)?;
+    let check = encrypt_with(&key, CHECK_PLAINTEXT, CHECK_AAD)?;
+
+    Ok((
+        VaultMeta {
+            salt: STANDARD.encode(salt),
+            kdf: params.encode(),
+            check,
+        },
+        key,
+    ))
+}
+
+/// 使用主密码打开账号库，校验值无法解密即视为密码错误。
+pub(crate) fn open(meta: &VaultMeta, password: &str) -> Result<VaultKey, VaultError> {
+    let params = KdfParams::decode(&meta.kdf).ok_or_else(|| {
+        VaultError::Crypto(format!("无法识别或超出上限的密钥派生参数: {}", meta.kdf))
+    })?;
+    let salt = STANDARD
+        .decode(&meta.salt)
+        .map_err(|e| VaultError::Crypto(e.to_string()))?;
+
+    let key = derive_key(password, &salt, &params)?;
+    match decrypt_with(&key, &meta.check, CHECK_AAD) {
+        Ok(check) if check == CHECK_PLAINTEXT => Ok(key),
+        _ => Err(VaultError::WrongPassword),
+    }
+}
+
+/// 用口令加密的一段数据，盐与密钥派生参数随密文一同保存。
+#[derive(Debug, Clone)]
+pub(crate) struct SealedData {
+    pub(crate) salt: String,
+    pub(crate) kdf: String,
+    pub(crate) ciphertext: String,
+}
+
+/// 用独立于主密码的口令加密整段数据（如备份文件），每次加密都生成新的盐。
+///
+/// `aad` 参与认证但不加密，调用方可以放入需要防篡改的明文头部信息。
+pub(crate) fn seal(
+    passphrase: &str,
+    plaintext: &str,
+    aad: &[u8],
+) -> Result<SealedData, VaultError> {
+    if passphrase.chars().count() < MIN_PASSWORD_LEN {
+        return Err(VaultError::WeakPassphrase);
+    }
+
+    let params = KdfParams::default();
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let key = derive_key(passphrase, &salt, &params)?;
+    Ok(SealedData {
+        salt: STANDARD.encode(salt),
+        kdf: params.encode(),
+        ciphertext: encrypt_with(&key, plaintext, aad)?,
+    })
+}
+
+/// 解密 `seal` 的结果；口令错误与内容被篡改无法区分，统一返回 `WrongPassphrase`。
+pub(crate) fn unseal(
+    sealed: &SealedData,
+    passphrase: &str,
+    aad: &[u8],
+) -> Result<String, VaultError> {
+    let params = KdfParams::decode(&sealed.kdf).ok_or_else(|| {
+        VaultError::Crypto(format!("无法识别或超出上限的密钥派生参数: {}", sealed.kdf))
+    })?;
+    let salt = STANDARD
+        .decode(&sealed.salt)
+        .map_err(|e| VaultError::Crypto(e.to_string()))?;
+    if !is_encrypted(&sealed.ciphertext) {
+        return Err(VaultError::WrongPassphrase);
+    }
+
+    let key = derive_key(passphrase, &salt, &params)?;
+    decrypt_with(&key, &sealed.ciphertext, aad).map_err(|_| VaultError::WrongPassphrase)
+}
+
+pub(crate) fn set_key(key: VaultKey) {
+    let mut state = VAULT_STATE.lock().unwrap();
+    state.key = Some(key);
+    state.last_activity = Instant::now();
+}
+
+/// 锁定账号库并清除内存中的密钥，返回锁定前是否处于解锁状态。
+pub(crate) fn lock() -> bool {
+    VAULT_STATE.lock().unwrap().key.take().is_some()
+}
+
+pub(crate) fn is_unlocked() -> bool {
+    VAULT_STATE.lock().unwrap().key.is_some()
+}
+
+/// 取得当前密钥的副本；账号库锁定时返回 `VaultError::Locked`。
+pub(crate) fn current_key() -> Result<VaultKey, VaultError> {
+    let mut state = VAULT_STATE.lock().unwrap();
+    let key = state
+        .key
+        .as_ref()
+        .map(|key| Zeroizing::new(**key))
+        .ok_or(VaultError::Locked)?;
+    state.last_activity = Instant::now();
+    Ok(key)
+}
+
+/// 访问不涉及解密的账号数据前调用：锁定时拒绝，解锁时刷新活动时间。
+pub(crate) fn ensure_unlocked() -> Result<(), VaultError> {
+    current_key().map(|_| ())
+}
+
+/// 记录一次用户活动，推迟自动锁定。
+pub(crate) fn touch() {
+    let mut state = VAULT_STATE.lock().unwrap();
+    if state.key.is_some() {
+        state.last_activity = Instant::now();
+    }
+}
+
+pub(crate) fn set_idle_timeout(secs: u64) {
+    VAULT_STATE.lock().unwrap().idle_timeout = Duration::from_secs(secs);
+}
+
+/// 闲置超时则锁定，返回本次是否触发了锁定。
+pub(crate) fn lock_if_idle() -> bool {
+    let mut state = VAULT_STATE.lock().unwrap();
+    if state.key.is_none() || state.idle_timeout.is_zero() {
+        return false;
+    }
+    if state.last_activity.elapsed() < state.idle_timeout {
+        return false;
+    }
+    state.key = None;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key(byte: u8) -> VaultKey {
+        Zeroizing::new([byte; KEY_LEN])
+    }
+
+    #[test]
+    fn encrypts_and_decrypts_with_account_bound_aad() {
+        let key = test_key(7);
+        let stored = encrypt_with(&key, "hunter2", &password_aad(1)).unwrap();
+        assert!(is_encrypted(&stored));
+        assert_eq!(
+            decrypt_with(&key, &stored, &password_aad(1)).unwrap(),
+            "hunter2"
+        );
+
+        // 挪到其他账号或其他字段上都无法解密
+        assert!(decrypt_with(&key, &stored, &password_aad(2)).is_err());
+        assert!(decrypt_with(&key, &stored, &username_aad(1)).is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_key_and_plaintext() {
+        let stored = encrypt_with(&test_key(1), "hunter2", &password_aad(1)).unwrap();
+        assert!(decrypt_with(&test_key(2), &stored, &password_aad(1)).is_err());
+        assert!(decrypt_with(&test_key(1), "hunter2", &password_aad(1)).is_err());
+    }
+
+    #[test]
+    fn kdf_params_above_limits_are_rejected() {
+        let default = KdfParams::default();
+        assert_eq!(KdfParams::decode(&default.encode()), Some(default));
+        assert_eq!(
+            KdfParams::decode("argon2id:m=77824,t=8,p=4"),
+            Some(KdfParams {
+                m_cost: 77824,
+                t_cost: 8,
+                p_cost: 4
+            })
+        );
+        assert_eq!(KdfParams::decode("argon2id:m=77825,t=2,p=1"), None);
+        assert_eq!(KdfParams::decode("argon2id:m=19456,t=9,p=1"), None);
+        assert_eq!(KdfParams::decode("argon2id:m=19456,t=2,p=5"), None);
+
+        let sealed = SealedData {
+            kdf: "argon2id:m=4294967295,t=2,p=1".to_string(),
+            ..seal("backup passphrase", "data", b"aad").unwrap()
+        };
+        assert!(matches!(
+            unseal(&sealed, "backup passphrase", b"aad"),
+            Err(VaultError::Crypto(_))
+        ));
+    }
+
+    #[test]
+    fn opens_only_with_the_master_password() {
+        let (meta, key) = create("correct horse").unwrap();
+        assert_eq!(*open(&meta, "correct horse").unwrap(), *key);
+        assert!(matches!(
+            open(&meta, "wrong horse"),
+            Err(VaultError::WrongPassword)
+        ));
+        assert!(matches!(create("short"), Err(VaultError::WeakPassword)));
+    }
+}
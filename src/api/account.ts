@@ -1,5 +1,5 @@
 import { invoke } from '@tauri-apps/api/tauri';
-import { Account } from '../entity/account';
+import { Account, AccountField } from '../entity/account';
 
 export async function queryAllAccounts(): Promise<Account[]> {
 	const res = await invoke('query_all_accounts');
@@ -9,37 +9,108 @@ export async function queryAllAccounts(): Promise<Account[]> {
 export async function queryAccountsByValue(
 	account: Account,
 	withLiked: boolean,
-	categoryId: number | null | undefined
+	categoryId: number | null | undefined,
+	roleServer: string | null = null,
+	roleZone: string | null = null
 ): Promise<Account[]> {
 	const res = await invoke('query_accounts_by_value', {
 		account,
 		withLiked,
-		categoryId
+		categoryId,
+		roleServer,
+		roleZone
 	});
 	return Array.isArray(res) ? (res as Account[]) : [];
 }
 
-export async function insertAccount(account: Account): Promise<boolean> {
-	const res = await invoke('insert_account', { account });
-	return !!res && typeof res === 'boolean' && res;
+export async function insertAccount(account: Account): Promise<void> {
+	await invoke('insert_account', { account });
 }
 
-export async function updateAccount(account: Account): Promise<boolean> {
-	const res = await invoke('update_account', { account });
-	return !!res && typeof res === 'boolean' && res;
+export async function updateAccount(account: Account): Promise<void> {
+	await invoke('update_account', { account });
 }
 
-export async function updateLike(id: number, liked: boolean): Promise<boolean> {
-	const res = await invoke('update_like', { id, liked });
-	return !!res && typeof res === 'boolean' && res;
+export async function updateLike(id: number, liked: boolean): Promise<void> {
+	await invoke('update_like', { id, liked });
 }
 
-export async function deleteAccount(id: number): Promise<boolean> {
-	const res = await invoke('delete_account', { id });
-	return !!res && typeof res === 'boolean' && res;
+export async function deleteAccount(id: number): Promise<void> {
+	await invoke('delete_account', { id });
 }
 
-export async function reorderAccounts(ids: number[]): Promise<boolean> {
-	const res = await invoke('reorder_accounts', { ids });
-	return !!res && typeof res === 'boolean' && res;
+export async function reorderAccounts(ids: number[]): Promise<void> {
+	await invoke('reorder_accounts', { ids });
+}
+
+export async function copyAccountField(
+	id: number,
+	field: AccountField
+): Promise<void> {
+	await invoke('copy_account_field', { id, field });
+}
+
+// 列映射的值为 CSV 列序号（从 0 开始），null 表示不导入该字段
+export interface CsvColumnMapping {
+	name: number | null;
+	username: number | null;
+	password: number | null;
+	description: number | null;
+	categories: number | null;
+	roles: number | null;
+}
+
+export type CsvDuplicateStrategy = 'skip' | 'overwrite' | 'create_new';
+
+export interface CsvPreview {
+	headers: string[];
+	rows: string[][];
+	total_rows: number;
+	suggested: CsvColumnMapping;
+}
+
+export interface CsvRowResult {
+	line: number;
+	name: string;
+	action: 'created' | 'updated' | 'skipped' | 'failed';
+	message: string | null;
+}
+
+export interface CsvImportReport {
+	dry_run: boolean;
+	created: number;
+	updated: number;
+	skipped: number;
+	failed: number;
+	categories_created: number;
+	rows: CsvRowResult[];
+}
+
+export async function exportAccountsCsv(path: string): Promise<number> {
+	return invoke<number>('export_accounts_csv', { path });
+}
+
+export async function previewAccountsCsv(path: string): Promise<CsvPreview> {
+	return invoke<CsvPreview>('preview_accounts_csv', { path });
+}
+
+export async function importAccountsCsv(options: {
+	path: string;
+	mapping: CsvColumnMapping;
+	duplicate: CsvDuplicateStrategy;
+	dry_run: boolean;
+}): Promise<CsvImportReport> {
+	return invoke<CsvImportReport>('import_accounts_csv', { options });
+}
+
+export type PasswordImportFormat = 'keepass_xml' | 'bitwarden_json' | 'browser_csv';
+
+// 导入结果与 CSV 导入相同，line 为条目序号（浏览器 CSV 为行号）
+export async function importPasswordManager(options: {
+	path: string;
+	format: PasswordImportFormat;
+	duplicate: CsvDuplicateStrategy;
+	dry_run: boolean;
+}): Promise<CsvImportReport> {
+	return invoke<CsvImportReport>('import_password_manager', { options });
 }
@@ -0,0 +1,35 @@
+export type AppErrorCode =
+	| 'not_found'
+	| 'validation'
+	| 'storage'
+	| 'network'
+	| 'locked'
+	| 'conflict';
+
+/** 后端命令失败时 reject 的错误对象 */
+export interface AppError {
+	code: AppErrorCode;
+	message: string;
+}
+
+export function isAppError(err: unknown): err is AppError {
+	return (
+		typeof err === 'object' &&
+		err !== null &&
+		typeof (err as AppError).code === 'string' &&
+		typeof (err as AppError).message === 'string'
+	);
+}
+
+export function errorMessage(err: unknown): string {
+	if (isAppError(err)) {
+		return err.message;
+	}
+	if (typeof err === 'string') {
+		return err;
+	}
+	if (err instanceof Error) {
+		return err.message;
+	}
+	return JSON.stringify(err);
+}
@@ -0,0 +1,83 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { Role } from '../entity/role';
+
+// amount 为正表示收入，为负表示支出；roleId 等均为角色表主键
+export interface LedgerEntry {
+	id: number | null;
+	role_id: number;
+	occurred_at: number;
+	currency: string;
+	amount: number;
+	counterparty_role_id: number | null;
+	note: string | null;
+}
+
+export interface LedgerTransfer {
+	from_role_id: number;
+	to_role_id: number;
+	currency: string;
+	amount: number;
+	occurred_at?: number | null;
+	note?: string | null;
+}
+
+export interface LedgerQuery {
+	account_id?: number | null;
+	role_id?: number | null;
+	currency?: string | null;
+}
+
+export interface LedgerCounterparty {
+	id: number;
+	role_id: string;
+	server: string;
+	account_name: string;
+}
+
+export interface LedgerRecord {
+	entry: LedgerEntry;
+	role: Role;
+	account_name: string;
+	counterparty: LedgerCounterparty | null;
+	role_balance: number;
+	account_balance: number;
+}
+
+export interface RoleLedgerBalance {
+	role: Role;
+	balance: number;
+}
+
+export interface AccountLedgerBalance {
+	account_id: number;
+	account_name: string;
+	currency: string;
+	balance: number;
+	roles: RoleLedgerBalance[];
+}
+
+export async function queryLedger(query: LedgerQuery): Promise<LedgerRecord[]> {
+	const res = await invoke('query_ledger', { query });
+	return Array.isArray(res) ? (res as LedgerRecord[]) : [];
+}
+
+export async function saveLedgerEntry(entry: LedgerEntry): Promise<number> {
+	return invoke<number>('save_ledger_entry', { entry });
+}
+
+export async function deleteLedgerEntry(id: number): Promise<void> {
+	await invoke('delete_ledger_entry', { id });
+}
+
+export async function recordLedgerTransfer(
+	transfer: LedgerTransfer
+): Promise<void> {
+	await invoke('record_ledger_transfer', { transfer });
+}
+
+export async function queryLedgerBalances(
+	accountId: number | null = null
+): Promise<AccountLedgerBalance[]> {
+	const res = await invoke('query_ledger_balances', { accountId });
+	return Array.isArray(res) ? (res as AccountLedgerBalance[]) : [];
+}
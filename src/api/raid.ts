@@ -0,0 +1,77 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { Role } from '../entity/role';
+import type { RoleSearchResult } from './role';
+
+export interface RaidInstance {
+	id: number | null;
+	name: string;
+	mode: string | null;
+	sequence: number | null;
+	enabled: boolean;
+}
+
+export interface RaidLockout {
+	raid_id: number;
+	raid_name: string;
+	role_id: number;
+	locked_at: number;
+	expires_at: number;
+}
+
+export interface RaidLockoutDetail {
+	lockout: RaidLockout;
+	role: Role;
+	account_name: string;
+}
+
+export async function queryRaidInstances(): Promise<RaidInstance[]> {
+	const res = await invoke('query_raid_instances');
+	return Array.isArray(res) ? (res as RaidInstance[]) : [];
+}
+
+export async function saveRaidInstance(raid: RaidInstance): Promise<RaidInstance> {
+	return invoke<RaidInstance>('save_raid_instance', { raid });
+}
+
+export async function deleteRaidInstance(id: number): Promise<void> {
+	await invoke('delete_raid_instance', { id });
+}
+
+// roleId 为角色表主键（Role.id）；lockedAt 为空时以当前时间记录
+export async function recordRaidLockout(
+	roleId: number,
+	raidId: number,
+	lockedAt: number | null = null
+): Promise<RaidLockout> {
+	return invoke<RaidLockout>('record_raid_lockout', {
+		roleId,
+		raidId,
+		lockedAt
+	});
+}
+
+export async function clearRaidLockout(
+	roleId: number,
+	raidId: number
+): Promise<void> {
+	await invoke('clear_raid_lockout', { roleId, raidId });
+}
+
+export async function queryRoleLockouts(roleId: number): Promise<RaidLockout[]> {
+	const res = await invoke('query_role_lockouts', { roleId });
+	return Array.isArray(res) ? (res as RaidLockout[]) : [];
+}
+
+export async function queryRaidLockouts(
+	raidId: number | null = null
+): Promise<RaidLockoutDetail[]> {
+	const res = await invoke('query_raid_lockouts', { raidId });
+	return Array.isArray(res) ? (res as RaidLockoutDetail[]) : [];
+}
+
+export async function queryRaidAvailableRoles(
+	raidId: number
+): Promise<RoleSearchResult[]> {
+	const res = await invoke('query_raid_available_roles', { raidId });
+	return Array.isArray(res) ? (res as RoleSearchResult[]) : [];
+}
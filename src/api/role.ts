@@ -0,0 +1,34 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { Role } from '../entity/role';
+
+export async function queryAccountRoles(accountId: number): Promise<Role[]> {
+	const res = await invoke('query_account_roles', { accountId });
+	return Array.isArray(res) ? (res as Role[]) : [];
+}
+
+export interface RoleQuery {
+	keyword?: string | null;
+	server?: string | null;
+	zone?: string | null;
+	school?: string | null;
+}
+
+export interface RoleSearchResult {
+	role: Role;
+	account_name: string;
+	zone: string | null;
+	status: string | null;
+}
+
+export async function searchRoles(query: RoleQuery): Promise<RoleSearchResult[]> {
+	const res = await invoke('search_roles', { query });
+	return Array.isArray(res) ? (res as RoleSearchResult[]) : [];
+}
+
+export async function saveRole(role: Role): Promise<Role> {
+	return invoke<Role>('save_role', { role });
+}
+
+export async function deleteRole(id: number): Promise<void> {
+	await invoke('delete_role', { id });
+}
@@ -13,14 +13,73 @@ interface NetworkSyncSettingsResponse {
 	last_sync: string | null;
 }
 
+export const SYNC_PROGRESS_EVENT = 'jx3-sync-progress';
+export const SYNC_FINISHED_EVENT = 'jx3-sync-finished';
+export const SERVERS_CHANGED_EVENT = 'jx3-servers-changed';
+export const WATCHED_SERVER_CHANGED_EVENT = 'jx3-watched-server-changed';
+
+export type SyncStage = 'fetching' | 'saving';
+
+export interface SyncProgress {
+	stage: SyncStage;
+	total: number;
+}
+
+export type SyncResult =
+	| 'not_modified'
+	| 'updated'
+	| 'fallback_used'
+	| 'failed'
+	| 'cancelled';
+
+export interface SyncSummary {
+	result: SyncResult;
+	updated: number;
+	error: string | null;
+}
+
+export interface Jx3ServerChange {
+	previous_status: string | null;
+	current: Jx3Server;
+}
+
+export interface SyncSummaryResponse {
+	result: SyncResult;
+	updated: number;
+	error: string | null;
+}
+
 export async function queryAllJx3Servers(): Promise<Jx3Server[]> {
 	const res = await invoke('query_all_jx3_servers');
 	return Array.isArray(res) ? (res as Jx3Server[]) : [];
 }
 
-export async function syncJx3Servers(forceFallback = false): Promise<boolean> {
-	const res = await invoke('sync_jx3_servers', { forceFallback });
-	return !!res;
+export async function setJx3ServerWatched(
+	server: string,
+	watched: boolean
+): Promise<void> {
+	await invoke('set_jx3_server_watched', { server, watched });
+}
+
+export async function syncJx3Servers(
+	forceFallback = false
+): Promise<SyncSummary> {
+	const result = await invoke<SyncSummaryResponse>('sync_jx3_servers', {
+		forceFallback
+	});
+	return toSyncSummary(result);
+}
+
+export async function cancelJx3Sync(): Promise<boolean> {
+	return invoke<boolean>('cancel_jx3_sync');
+}
+
+export function toSyncSummary(result: SyncSummaryResponse): SyncSummary {
+	return {
+		result: result.result,
+		updated: result.updated,
+		error: result.error
+	};
 }
 
 export async function getNetworkSyncSettings(): Promise<NetworkSyncSettings> {
@@ -37,10 +96,166 @@ export async function getNetworkSyncSettings(): Promise<NetworkSyncSettings> {
 export async function saveNetworkSyncSettings(
 	enabled: boolean,
 	prompted: boolean
-): Promise<boolean> {
-	const res = await invoke<boolean>('save_network_sync_settings', {
+): Promise<void> {
+	await invoke('save_network_sync_settings', {
 		enabled,
 		prompted
 	});
-	return !!res;
+}
+
+export async function getJx3SyncInterval(): Promise<number> {
+	return await invoke<number>('get_jx3_sync_interval');
+}
+
+export async function saveJx3SyncInterval(secs: number): Promise<void> {
+	await invoke('save_jx3_sync_interval', { secs });
+}
+
+export interface Jx3ServerStatusRecord {
+	status: string;
+	changed_at: number;
+}
+
+export interface Jx3ServerStatusShare {
+	status: string;
+	seconds: number;
+	percentage: number;
+}
+
+export interface Jx3ServerUptime {
+	server: string;
+	from: number;
+	to: number;
+	observed_seconds: number;
+	statuses: Jx3ServerStatusShare[];
+}
+
+export interface Jx3ServerMaintenance {
+	server: string;
+	started_at: number | null;
+	ended_at: number | null;
+}
+
+export async function queryJx3ServerTimeline(
+	server: string,
+	from: number | null = null,
+	to: number | null = null
+): Promise<Jx3ServerStatusRecord[]> {
+	return await invoke<Jx3ServerStatusRecord[]>('query_jx3_server_timeline', {
+		server,
+		from,
+		to
+	});
+}
+
+export async function getJx3ServerUptime(
+	server: string,
+	windowSecs: number
+): Promise<Jx3ServerUptime> {
+	return await invoke<Jx3ServerUptime>('get_jx3_server_uptime', {
+		server,
+		windowSecs
+	});
+}
+
+export async function getJx3ServerLastMaintenance(
+	server: string
+): Promise<Jx3ServerMaintenance> {
+	return await invoke<Jx3ServerMaintenance>('get_jx3_server_last_maintenance', {
+		server
+	});
+}
+
+export interface FieldMapping {
+	list_path: string;
+	zone: string;
+	server: string;
+	status: string;
+}
+
+export type ProviderConfig =
+	| { kind: 'jx3_api' }
+	| { kind: 'json_endpoint'; url: string; mapping: FieldMapping }
+	| { kind: 'local_file'; path: string; mapping: FieldMapping };
+
+export function getDefaultFieldMapping(): FieldMapping {
+	return { list_path: '', zone: 'zone', server: 'server', status: 'status' };
+}
+
+export async function getJx3ProviderConfig(): Promise<ProviderConfig> {
+	return await invoke<ProviderConfig>('get_jx3_provider_config');
+}
+
+export async function saveJx3ProviderConfig(
+	config: ProviderConfig
+): Promise<void> {
+	await invoke('save_jx3_provider_config', { config });
+}
+
+export async function getJx3MaintenanceStatus(): Promise<string> {
+	return await invoke<string>('get_jx3_maintenance_status');
+}
+
+export async function saveJx3MaintenanceStatus(status: string): Promise<void> {
+	await invoke('save_jx3_maintenance_status', { status });
+}
+
+export async function testJx3Provider(config: ProviderConfig): Promise<number> {
+	return await invoke<number>('test_jx3_provider', { config });
+}
+
+export interface FallbackInfo {
+	version: number;
+	source: 'builtin' | 'bundled' | 'override';
+	server_count: number;
+	override_path: string | null;
+	override_error: string | null;
+}
+
+export async function getJx3FallbackInfo(): Promise<FallbackInfo> {
+	return await invoke<FallbackInfo>('get_jx3_fallback_info');
+}
+
+export interface Jx3ServerMerge {
+	old_server: string;
+	new_server: string;
+	effective_date: string;
+	applied_at: number | null;
+}
+
+export interface MigratedRole {
+	role_id: string;
+	old_server: string;
+	new_server: string;
+}
+
+export interface AffectedAccount {
+	account_id: number;
+	name: string;
+	roles: MigratedRole[];
+}
+
+export interface MergeReport {
+	dry_run: boolean;
+	applied_merges: Jx3ServerMerge[];
+	migrated_roles: number;
+	affected_accounts: AffectedAccount[];
+}
+
+export async function queryJx3ServerMerges(): Promise<Jx3ServerMerge[]> {
+	return await invoke<Jx3ServerMerge[]>('query_jx3_server_merges');
+}
+
+export async function saveJx3ServerMerge(merge: Jx3ServerMerge): Promise<void> {
+	await invoke('save_jx3_server_merge', { merge });
+}
+
+export async function deleteJx3ServerMerge(oldServer: string): Promise<void> {
+	await invoke('delete_jx3_server_merge', { oldServer });
+}
+
+export async function applyJx3ServerMerges(
+	dryRun: boolean
+): Promise<MergeReport> {
+	return await invoke<MergeReport>('apply_jx3_server_merges', { dryRun });
 }
@@ -20,12 +20,11 @@ export async function getDisplaySettings(): Promise<DisplaySettings> {
 
 export async function saveDisplaySettings(
 	settings: DisplaySettings
-): Promise<boolean> {
-	const result = await invoke<boolean>('save_display_settings', {
+): Promise<void> {
+	await invoke('save_display_settings', {
 		hideUsername: settings.hideUsername,
 		hidePassword: settings.hidePassword
 	});
-	return !!result;
 }
 
 export async function getAppVersion(): Promise<string> {
@@ -41,9 +40,8 @@ export async function getExportFields(): Promise<string[]> {
 	return Array.isArray(result) && result.length > 0 ? result : [];
 }
 
-export async function saveExportFields(fields: string[]): Promise<boolean> {
-	const result = await invoke<boolean>('save_export_fields', { fields });
-	return !!result;
+export async function saveExportFields(fields: string[]): Promise<void> {
+	await invoke('save_export_fields', { fields });
 }
 
 export async function getFavoriteFilter(): Promise<number> {
@@ -51,7 +49,47 @@ export async function getFavoriteFilter(): Promise<number> {
 	return typeof result === 'number' ? result : 0;
 }
 
-export async function saveFavoriteFilter(value: number): Promise<boolean> {
-	const result = await invoke<boolean>('save_favorite_filter', { value });
-	return !!result;
+export async function saveFavoriteFilter(value: number): Promise<void> {
+	await invoke('save_favorite_filter', { value });
+}
+
+export async function getClipboardClearSeconds(): Promise<number> {
+	const result = await invoke<number>('get_clipboard_clear_seconds');
+	return typeof result === 'number' ? result : 30;
+}
+
+export async function saveClipboardClearSeconds(secs: number): Promise<void> {
+	await invoke('save_clipboard_clear_seconds', { secs });
+}
+
+// 剪贴板统一由后端写入，sensitive 为 true 时按设置自动清除
+export async function copyText(text: string, sensitive = false): Promise<void> {
+	await invoke('copy_text', { text, sensitive });
+}
+
+export interface DatabaseLocation {
+	path: string;
+	source: 'env' | 'setting' | 'default';
+	defaultPath: string;
+}
+
+interface DatabaseLocationResponse {
+	path: string;
+	source: 'env' | 'setting' | 'default';
+	default_path: string;
+}
+
+export async function getDatabaseLocation(): Promise<DatabaseLocation> {
+	const result = await invoke<DatabaseLocationResponse>(
+		'get_database_location'
+	);
+	return {
+		path: result.path,
+		source: result.source,
+		defaultPath: result.default_path
+	};
+}
+
+export async function setDatabasePath(path: string | null): Promise<void> {
+	await invoke('set_database_path', { path });
 }
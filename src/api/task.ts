@@ -0,0 +1,79 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import { Role } from '../entity/role';
+
+export type TaskPeriod = 'daily' | 'weekly';
+
+export interface RoleTask {
+	id: number | null;
+	name: string;
+	period: TaskPeriod;
+	description: string | null;
+	sequence: number | null;
+	enabled: boolean;
+}
+
+export interface RoleTaskProgress {
+	task: RoleTask;
+	completed_at: number | null;
+	reset_at: number;
+}
+
+export interface OutstandingTask {
+	task_id: number;
+	task_name: string;
+	period: TaskPeriod;
+	reset_at: number;
+	role: Role;
+	account_name: string;
+}
+
+export interface ResetSchedule {
+	daily_start: number;
+	next_daily_reset: number;
+	weekly_start: number;
+	next_weekly_reset: number;
+}
+
+export async function getTaskResetSchedule(): Promise<ResetSchedule> {
+	return invoke<ResetSchedule>('get_task_reset_schedule');
+}
+
+export async function queryRoleTasks(): Promise<RoleTask[]> {
+	const res = await invoke('query_role_tasks');
+	return Array.isArray(res) ? (res as RoleTask[]) : [];
+}
+
+export async function saveRoleTask(task: RoleTask): Promise<RoleTask> {
+	return invoke<RoleTask>('save_role_task', { task });
+}
+
+export async function deleteRoleTask(id: number): Promise<void> {
+	await invoke('delete_role_task', { id });
+}
+
+// roleId 为角色表主键（Role.id），不是游戏内的角色 ID
+export async function setRoleTaskCompleted(
+	roleId: number,
+	taskId: number,
+	completed: boolean
+): Promise<number | null> {
+	return invoke<number | null>('set_role_task_completed', {
+		roleId,
+		taskId,
+		completed
+	});
+}
+
+export async function queryRoleTaskProgress(
+	roleId: number
+): Promise<RoleTaskProgress[]> {
+	const res = await invoke('query_role_task_progress', { roleId });
+	return Array.isArray(res) ? (res as RoleTaskProgress[]) : [];
+}
+
+export async function queryOutstandingTasks(
+	period: TaskPeriod | null = null
+): Promise<OutstandingTask[]> {
+	const res = await invoke('query_outstanding_tasks', { period });
+	return Array.isArray(res) ? (res as OutstandingTask[]) : [];
+}
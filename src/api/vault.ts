@@ -0,0 +1,153 @@
+import { invoke } from '@tauri-apps/api/tauri';
+
+export interface VaultStatus {
+	initialized: boolean;
+	unlocked: boolean;
+	pendingEncryption: boolean;
+}
+
+interface VaultStatusResponse {
+	initialized: boolean;
+	unlocked: boolean;
+	pending_encryption: boolean;
+}
+
+export async function getVaultStatus(): Promise<VaultStatus> {
+	const result = await invoke<VaultStatusResponse>('get_vault_status');
+	return {
+		initialized: result.initialized,
+		unlocked: result.unlocked,
+		pendingEncryption: result.pending_encryption
+	};
+}
+
+export async function setupVault(password: string): Promise<void> {
+	await invoke('setup_vault', { password });
+}
+
+export async function unlockVault(password: string): Promise<void> {
+	await invoke('unlock_vault', { password });
+}
+
+export async function lockVault(): Promise<void> {
+	await invoke('lock_vault');
+}
+
+export async function reportVaultActivity(): Promise<void> {
+	await invoke('report_vault_activity');
+}
+
+export async function getVaultIdleTimeout(): Promise<number> {
+	const result = await invoke<number>('get_vault_idle_timeout');
+	return typeof result === 'number' ? result : 300;
+}
+
+export async function saveVaultIdleTimeout(secs: number): Promise<void> {
+	await invoke('save_vault_idle_timeout', { secs });
+}
+
+export interface VaultProfile {
+	id: string;
+	name: string;
+	path: string;
+}
+
+export interface VaultProfileList {
+	active: string;
+	profiles: VaultProfile[];
+}
+
+export async function listVaultProfiles(): Promise<VaultProfileList> {
+	return await invoke<VaultProfileList>('list_vault_profiles');
+}
+
+export async function createVaultProfile(name: string): Promise<void> {
+	await invoke('create_vault_profile', { name });
+}
+
+export async function renameVaultProfile(
+	id: string,
+	name: string
+): Promise<void> {
+	await invoke('rename_vault_profile', { id, name });
+}
+
+export async function deleteVaultProfile(id: string): Promise<void> {
+	await invoke('delete_vault_profile', { id });
+}
+
+export async function switchVaultProfile(id: string): Promise<void> {
+	await invoke('switch_vault_profile', { id });
+}
+
+export interface VaultTransferSummary {
+	accounts: number;
+	categories: number;
+	roles: number;
+	settings: number;
+	role_tasks: number;
+	raids: number;
+	ledger_entries: number;
+	skipped: number;
+}
+
+// 导出文件中的用户名与密码为明文
+export async function exportVault(path: string): Promise<VaultTransferSummary> {
+	return invoke<VaultTransferSummary>('export_vault', { path });
+}
+
+export async function importVault(path: string): Promise<VaultTransferSummary> {
+	return invoke<VaultTransferSummary>('import_vault', { path });
+}
+
+export interface BackupSummary {
+	app_version: string;
+	// Unix 时间戳（秒）
+	created_at: number;
+	contents: VaultTransferSummary;
+}
+
+export type RestoreMode = 'replace' | 'merge';
+
+export async function createBackup(path: string, passphrase: string): Promise<BackupSummary> {
+	return invoke<BackupSummary>('create_backup', { path, passphrase });
+}
+
+export async function inspectBackup(path: string, passphrase: string): Promise<BackupSummary> {
+	return invoke<BackupSummary>('inspect_backup', { path, passphrase });
+}
+
+export async function restoreBackup(
+	path: string,
+	passphrase: string,
+	mode: RestoreMode
+): Promise<VaultTransferSummary> {
+	return invoke<VaultTransferSummary>('restore_backup', { path, passphrase, mode });
+}
+
+export type SnapshotReason = 'migration' | 'import' | 'delete' | 'daily' | 'restore';
+
+export interface SnapshotInfo {
+	name: string;
+	reason: SnapshotReason;
+	// Unix 时间戳（秒）
+	created_at: number;
+	size: number;
+}
+
+export async function listSnapshots(): Promise<SnapshotInfo[]> {
+	return invoke<SnapshotInfo[]>('list_snapshots');
+}
+
+// 恢复后账号库会被锁定，并通过 vault-locked 事件通知界面
+export async function restoreSnapshot(name: string): Promise<void> {
+	await invoke('restore_snapshot', { name });
+}
+
+export async function getSnapshotRetention(): Promise<number> {
+	return invoke<number>('get_snapshot_retention');
+}
+
+export async function saveSnapshotRetention(count: number): Promise<void> {
+	await invoke('save_snapshot_retention', { count });
+}
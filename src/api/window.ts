@@ -19,10 +19,9 @@ export async function getDefaultWindowSize(): Promise<WindowSize> {
 	return await invoke<WindowSize>('get_default_window_size');
 }
 
-export async function saveWindowSizeToDb(size: WindowSize): Promise<boolean> {
-	const result = await invoke<boolean>('save_window_size', {
+export async function saveWindowSizeToDb(size: WindowSize): Promise<void> {
+	await invoke('save_window_size', {
 		width: size.width,
 		height: size.height
 	});
-	return !!result;
 }
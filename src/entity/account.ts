@@ -1,6 +1,9 @@
 import { Role } from './role';
 
 export { Account };
+export type { AccountField };
+
+type AccountField = 'username' | 'password';
 
 class Account {
 	public id: number | null = null;
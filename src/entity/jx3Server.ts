@@ -3,6 +3,7 @@ export class Jx3Server {
 	public zone: string = '';
 	public server: string = '';
 	public status: string = '';
+	public watched: boolean = false;
 
 	constructor(zone = '', server = '', status = '') {
 		this.zone = zone;
@@ -3,6 +3,12 @@ export class Role {
 	public account_id: number | null = null;
 	public role_id: string = '';
 	public server: string = '';
+	public name: string | null = null;
+	public school: string | null = null;
+	public body_type: string | null = null;
+	public level: number | null = null;
+	public equipment_score: number | null = null;
+	public notes: string | null = null;
 
 	constructor(roleId = '', server = '') {
 		this.role_id = roleId;
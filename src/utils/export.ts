@@ -1,5 +1,8 @@
 import { Account } from '../entity/account';
+import type { AccountLedgerBalance, LedgerRecord } from '../api/ledger';
 import { Jx3Server } from '../entity/jx3Server';
+import { Role } from '../entity/role';
+import { formatRoleSummary } from './jx3Role';
 
 export const EXPORT_FIELD_OPTIONS = [
 	{ value: 'name', label: '名称' },
@@ -7,6 +10,7 @@ export const EXPORT_FIELD_OPTIONS = [
 	{ value: 'password', label: '密码' },
 	{ value: 'roles', label: '角色区服' },
 	{ value: 'description', label: '备注' },
+	{ value: 'ledger', label: '账本余额' },
 	{ value: 'liked', label: '收藏状态' }
 ] as const;
 
@@ -31,22 +35,31 @@ function findServerMeta(
 	return servers.find((item) => item.server === serverName);
 }
 
-function formatRoleLine(
-	roleId: string,
-	serverName: string,
-	servers: Jx3Server[]
-): string {
-	const meta = findServerMeta(serverName, servers);
-	if (meta) {
-		return `  - ${roleId} @ ${meta.zone}·${meta.server} (${meta.status})`;
-	}
-	return `  - ${roleId} @ ${serverName}`;
+function formatRoleLine(role: Role, servers: Jx3Server[]): string {
+	const meta = findServerMeta(role.server, servers);
+	const line = meta
+		? `  - ${role.role_id} @ ${meta.zone}·${meta.server} (${meta.status})`
+		: `  - ${role.role_id} @ ${role.server}`;
+	const summary = formatRoleSummary(role);
+	return summary ? `${line} [${summary}]` : line;
+}
+
+function formatAmount(amount: number): string {
+	return amount > 0 ? `+${amount}` : String(amount);
+}
+
+function formatDate(secs: number): string {
+	const date = new Date(secs * 1000);
+	const pad = (value: number) => String(value).padStart(2, '0');
+	return `${date.getFullYear()}-${pad(date.getMonth() + 1)}-${pad(date.getDate())}`;
 }
 
+// ledger 为该账号的账本余额（queryLedgerBalances），仅在选择了“账本余额”字段时使用
 export function formatAccountForShare(
 	account: Account,
 	fields: ExportField[],
-	servers: Jx3Server[] = []
+	servers: Jx3Server[] = [],
+	ledger: AccountLedgerBalance[] = []
 ): string {
 	const lines: string[] = [];
 	const fieldSet = new Set(fields);
@@ -68,7 +81,7 @@ export function formatAccountForShare(
 		if (roles.length > 0) {
 			lines.push('角色区服:');
 			for (const role of roles) {
-				lines.push(formatRoleLine(role.role_id, role.server, servers));
+				lines.push(formatRoleLine(role, servers));
 			}
 		} else {
 			lines.push('角色区服: 无');
@@ -77,6 +90,32 @@ export function formatAccountForShare(
 	if (fieldSet.has('description')) {
 		lines.push(`备注: ${account.description ?? ''}`);
 	}
+	if (fieldSet.has('ledger')) {
+		if (ledger.length > 0) {
+			lines.push('账本余额:');
+			for (const item of ledger) {
+				const roles = item.roles
+					.map((r) => `${r.role.role_id} ${r.balance}`)
+					.join('，');
+				lines.push(`  - ${item.currency}: ${item.balance}（${roles}）`);
+			}
+		} else {
+			lines.push('账本余额: 无');
+		}
+	}
 
 	return lines.join('\n');
 }
+
+// 账本明细按记录顺序逐行输出，附带记录后的角色余额
+export function formatLedgerForShare(records: LedgerRecord[]): string {
+	return records
+		.map((record) => {
+			const counterparty = record.counterparty
+				? ` ↔ ${record.counterparty.role_id} @ ${record.counterparty.server}`
+				: '';
+			const note = record.entry.note ? ` ${record.entry.note}` : '';
+			return `${formatDate(record.entry.occurred_at)} ${record.account_name} · ${record.role.role_id} @ ${record.role.server} ${record.entry.currency} ${formatAmount(record.entry.amount)}（余额 ${record.role_balance}）${counterparty}${note}`;
+		})
+		.join('\n');
+}
@@ -0,0 +1,47 @@
+import { Role } from '../entity/role';
+
+export const JX3_SCHOOLS = [
+	'七秀',
+	'万花',
+	'纯阳',
+	'天策',
+	'少林',
+	'藏剑',
+	'五毒',
+	'唐门',
+	'明教',
+	'丐帮',
+	'苍云',
+	'长歌',
+	'霸刀',
+	'蓬莱',
+	'凌雪阁',
+	'衍天宗',
+	'药宗',
+	'刀宗',
+	'万灵',
+	'段氏'
+];
+
+export const JX3_BODY_TYPES = ['成男', '成女', '正太', '萝莉'];
+
+// 角色名、门派、体型、等级、装分中已填写的部分，如“奶妈·万花·成女·130级”
+export function formatRoleSummary(role: Role): string {
+	const parts: string[] = [];
+	if (role.name) {
+		parts.push(role.name);
+	}
+	if (role.school) {
+		parts.push(role.school);
+	}
+	if (role.body_type) {
+		parts.push(role.body_type);
+	}
+	if (role.level) {
+		parts.push(`${role.level}级`);
+	}
+	if (role.equipment_score) {
+		parts.push(`装分${role.equipment_score}`);
+	}
+	return parts.join('·');
+}